@@ -0,0 +1,39 @@
+//! Solving a batch of puzzles one after another versus with `solve_all`, to
+//! see how the parallel version scales with the number of cores.
+
+mod common;
+
+use common::STOLEN;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use twonottouch::{solve_all, Board};
+
+fn bench_batch(c: &mut Criterion) {
+    let board = STOLEN.parse::<Board>().unwrap();
+    let mut group = c.benchmark_group("solve batch");
+    for size in [16, 64, 256] {
+        let boards = vec![board.clone(); size];
+        group.bench_with_input(
+            BenchmarkId::new("sequential", size),
+            &boards,
+            |b, boards| {
+                b.iter(|| {
+                    boards
+                        .clone()
+                        .into_iter()
+                        .map(|mut board| {
+                            let outcome = board.solve_complete();
+                            (board, outcome)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("solve_all", size), &boards, |b, boards| {
+            b.iter(|| solve_all(boards.clone()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch);
+criterion_main!(benches);
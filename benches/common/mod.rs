@@ -0,0 +1,26 @@
+//! Puzzles shared by the benches. Each bench builds this module on its own,
+//! and not every one of them uses every puzzle.
+#![allow(dead_code)]
+
+/// A 10x10 two-star puzzle the deductions can't finish alone, so solving it
+/// takes some guessing.
+pub const STOLEN: &str = "
+    0011222333
+    0012222323
+    0011112223
+    0111445222
+    0111455552
+    0666444422
+    7677444882
+    7777499982
+    7778888882
+    7778888888
+";
+
+/// 2x2 regions can't hold two stars each, so the search has to rule out every guess.
+pub const SQUARES: &str = "
+    0011
+    0011
+    2233
+    2233
+";
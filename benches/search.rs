@@ -0,0 +1,17 @@
+//! End-to-end solving, which leans on the contradiction checks after every guess.
+
+mod common;
+
+use common::{SQUARES, STOLEN};
+use criterion::{criterion_group, criterion_main, Criterion};
+use twonottouch::Board;
+
+fn bench_search(c: &mut Criterion) {
+    let stolen = STOLEN.parse::<Board>().unwrap();
+    let squares = SQUARES.parse::<Board>().unwrap();
+    c.bench_function("solve 10x10", |b| b.iter(|| stolen.find_solution()));
+    c.bench_function("refute 4x4", |b| b.iter(|| squares.find_solution()));
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
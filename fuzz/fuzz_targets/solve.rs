@@ -0,0 +1,30 @@
+//! Arbitrary region grids through the checked constructor and the solver.
+//! Input is a width byte, a height byte, then one region tag per cell.
+
+#![no_main]
+
+use std::sync::atomic::AtomicBool;
+
+use libfuzzer_sys::fuzz_target;
+use twonottouch::Board;
+
+fuzz_target!(|data: &[u8]| {
+    let [width, height, tags @ ..] = data else {
+        return;
+    };
+    // keep boards small enough to search
+    let (width, height) = (usize::from(width % 12), usize::from(height % 12));
+    if tags.len() < width * height {
+        return;
+    }
+    let regions = tags[..width * height]
+        .iter()
+        .map(|&tag| usize::from(tag % 16))
+        .collect::<Vec<_>>();
+
+    // an error is fine; a panic is what we're after
+    let Ok(mut board) = Board::from_flat(width, &regions) else {
+        return;
+    };
+    board.solve_with_cancel(&AtomicBool::new(false));
+});
@@ -0,0 +1,18 @@
+//! Solving a whole collection of puzzles at once, spread across every core.
+
+use rayon::prelude::*;
+
+use crate::{Board, SolveOutcome};
+
+/// Solves every board with `solve_complete`, in parallel, and hands each one
+/// back in its solved state alongside how it went. The results come back in
+/// the same order as `boards`.
+pub fn solve_all(boards: Vec<Board>) -> Vec<(Board, SolveOutcome)> {
+    boards
+        .into_par_iter()
+        .map(|mut board| {
+            let outcome = board.solve_complete();
+            (board, outcome)
+        })
+        .collect()
+}
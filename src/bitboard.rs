@@ -0,0 +1,40 @@
+//! Stars and shading packed one bit per cell, for telling cheaply whether a
+//! pass of the rules changed anything. Each row is a `u128`, so boards wider
+//! than 128 cells don't fit and get compared cell by cell instead.
+
+use crate::{Board, CellState};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BitBoard {
+    /// bit `col` of `stars[row]` is set when the cell holds a star
+    stars: Vec<u128>,
+    /// likewise for shaded cells
+    filled: Vec<u128>,
+}
+
+impl BitBoard {
+    pub(crate) const MAX_WIDTH: usize = u128::BITS as usize;
+
+    /// Packs the board's cells, or `None` if it's too wide.
+    pub(crate) fn new(board: &Board) -> Option<BitBoard> {
+        if board.width > Self::MAX_WIDTH {
+            return None;
+        }
+        let pack = |state: CellState| {
+            board
+                .cells
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_col, cell)| cell.state == state)
+                        .fold(0, |bits, (col, _cell)| bits | 1 << col)
+                })
+                .collect()
+        };
+        Some(BitBoard {
+            stars: pack(CellState::Star),
+            filled: pack(CellState::Filled),
+        })
+    }
+}
@@ -0,0 +1,95 @@
+//! Plain-text rendering with the regions outlined in box-drawing characters,
+//! for reading a big board in a terminal.
+
+use crate::{Board, CellState};
+
+/// Crossings of four lines, indexed by which arms are heavy: up, down, left
+/// and right in the bits from lowest.
+const CROSSINGS: [char; 16] = [
+    '┼', '╀', '╁', '╂', '┽', '╃', '╅', '╉', '┾', '╄', '╆', '╊', '┿', '╇', '╈', '╋',
+];
+
+impl Board {
+    /// Prints `to_bordered_string`.
+    pub fn print_bordered(&self) {
+        print!("{}", self.to_bordered_string());
+    }
+
+    /// The board drawn cell by cell, with heavy lines between regions and
+    /// around the edge and light ones inside each region. Stars are `X`,
+    /// shaded cells `#` and blanks `·`.
+    pub fn to_bordered_string(&self) -> String {
+        // the line down the left of `col` (or the right of the last column)
+        let heavy_down = |row: usize, col: usize| {
+            col == 0
+                || col == self.width
+                || self.region_at(row, col - 1) != self.region_at(row, col)
+        };
+        // the line along the top of `row` (or the bottom of the last row)
+        let heavy_across = |row: usize, col: usize| {
+            row == 0
+                || row == self.height
+                || self.region_at(row - 1, col) != self.region_at(row, col)
+        };
+
+        let mut text = String::new();
+        for row in 0..=self.height {
+            for col in 0..=self.width {
+                let up = (row > 0).then(|| heavy_down(row - 1, col));
+                let down = (row < self.height).then(|| heavy_down(row, col));
+                let left = (col > 0).then(|| heavy_across(row, col - 1));
+                let right = (col < self.width).then(|| heavy_across(row, col));
+                text.push(junction(up, down, left, right));
+                if col < self.width {
+                    text.push_str(if heavy_across(row, col) {
+                        "━━━"
+                    } else {
+                        "───"
+                    });
+                }
+            }
+            text.push('\n');
+            if row == self.height {
+                break;
+            }
+            for col in 0..=self.width {
+                text.push(if heavy_down(row, col) { '┃' } else { '│' });
+                if col < self.width {
+                    let glyph = match self.cells[row][col].state {
+                        CellState::Star => 'X',
+                        CellState::Filled => '#',
+                        CellState::Blank => '·',
+                    };
+                    text.push_str(&format!(" {glyph} "));
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The character where lines meet, given which arms it has and whether each
+/// is heavy. The edge of the board is always heavy, so only the arm running
+/// inwards from an edge can be light.
+fn junction(up: Option<bool>, down: Option<bool>, left: Option<bool>, right: Option<bool>) -> char {
+    match (up, down, left, right) {
+        (Some(up), Some(down), Some(left), Some(right)) => {
+            CROSSINGS
+                [up as usize | (down as usize) << 1 | (left as usize) << 2 | (right as usize) << 3]
+        }
+        (None, Some(_), None, Some(_)) => '┏',
+        (None, Some(_), Some(_), None) => '┓',
+        (Some(_), None, None, Some(_)) => '┗',
+        (Some(_), None, Some(_), None) => '┛',
+        (None, Some(true), _, _) => '┳',
+        (None, Some(false), _, _) => '┯',
+        (Some(true), None, _, _) => '┻',
+        (Some(false), None, _, _) => '┷',
+        (_, _, None, Some(true)) => '┣',
+        (_, _, None, Some(false)) => '┠',
+        (_, _, Some(true), None) => '┫',
+        (_, _, Some(false), None) => '┨',
+        _ => ' ',
+    }
+}
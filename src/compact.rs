@@ -0,0 +1,142 @@
+//! A one-line encoding of a board mid-solve, for logs and regression fixtures.
+//!
+//! `4x4;r:0,0,1,1/0,0,1,1/2,2,3,3/2,2,3,3;q:2,2,2,2;s:R0C0;f:R0C1,R1C0,R1C1`
+//! is the size, `;wrap` if the board wraps, `;orthogonal` if stars only touch
+//! through their edges, `;n:{stars}` if rows and columns
+//! hold other than two stars, then the region tags row by row,
+//! each region's star quota by tag, and the stars and shaded cells as
+//! `R{row}C{col}` from 0. Any list may be empty.
+
+use crate::{AdjacencyMode, Board, BoardError, CellState};
+
+impl Board {
+    /// Encodes the board, cell states included, on a single line that
+    /// `from_compact` turns back into an equal board.
+    pub fn to_compact(&self) -> String {
+        let mut result = format!("{}x{}", self.width, self.height);
+        if self.wrap {
+            result.push_str(";wrap");
+        }
+        if self.adjacency == AdjacencyMode::Orthogonal {
+            result.push_str(";orthogonal");
+        }
+        if self.stars_per_unit != 2 {
+            result.push_str(&format!(";n:{}", self.stars_per_unit));
+        }
+        let tags = self
+            .cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.region.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>();
+        result.push_str(&format!(";r:{}", tags.join("/")));
+        let quotas = self
+            .region_star_counts
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>();
+        result.push_str(&format!(";q:{}", quotas.join(",")));
+        result.push_str(&format!(";s:{}", self.cells_labelled(CellState::Star)));
+        result.push_str(&format!(";f:{}", self.cells_labelled(CellState::Filled)));
+        result
+    }
+
+    /// Parses a board from its `to_compact` encoding. The region map has to
+    /// pass the same checks as `Board::try_from`, unless it's the single
+    /// region of a `Board::empty`.
+    pub fn from_compact(s: &str) -> Result<Board, BoardError> {
+        let mut fields = s.trim().split(';').peekable();
+        let header = fields.next().unwrap_or_default();
+        let (width, height): (usize, usize) = header
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or_else(|| parse_failure(format!("malformed size `{header}`")))?;
+        let wrap = fields.next_if_eq(&"wrap").is_some();
+        let adjacency = match fields.next_if_eq(&"orthogonal") {
+            Some(_) => AdjacencyMode::Orthogonal,
+            None => AdjacencyMode::King,
+        };
+        let stars_per_unit = match fields.next_if(|field| field.starts_with("n:")) {
+            Some(field) => field[2..]
+                .parse()
+                .map_err(|_| parse_failure(format!("malformed star count `{field}`")))?,
+            None => 2,
+        };
+        let mut field = |key: &str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(key)?.strip_prefix(':'))
+                .ok_or_else(|| parse_failure(format!("missing `{key}:` field")))
+        };
+        let (tags, quotas, stars, filled) = (field("r")?, field("q")?, field("s")?, field("f")?);
+        if let Some(extra) = fields.next() {
+            return Err(parse_failure(format!("unexpected field `{extra}`")));
+        }
+
+        let regions = tags
+            .split('/')
+            .map(|row| numbers(row, "region tag"))
+            .collect::<Result<Vec<_>, _>>()?;
+        if (regions.first().map_or(0, Vec::len), regions.len()) != (width, height) {
+            return Err(parse_failure(format!(
+                "the region map isn't {width}x{height}"
+            )));
+        }
+        let mut board = Board::from_stored_regions(regions)?;
+        board.set_wrap(wrap);
+        board.set_adjacency_mode(adjacency);
+        board.set_stars_per_unit(stars_per_unit);
+        board.set_region_star_counts(numbers(quotas, "quota")?)?;
+        for (list, state) in [(stars, CellState::Star), (filled, CellState::Filled)] {
+            for (row, col) in coordinates(list, width, height)? {
+                board.set_state(row, col, state);
+            }
+        }
+        board.regenerate_regions();
+        Ok(board)
+    }
+
+    fn cells_labelled(&self, state: CellState) -> String {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cells[row][col].state == state)
+            .map(|(row, col)| format!("R{row}C{col}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn parse_failure(reason: impl Into<String>) -> BoardError {
+    BoardError::ParseFailure(reason.into())
+}
+
+/// A comma-separated list of numbers, which may be empty.
+fn numbers(list: &str, what: &str) -> Result<Vec<usize>, BoardError> {
+    list.split(',')
+        .filter(|number| !number.is_empty())
+        .map(|number| {
+            number
+                .parse()
+                .map_err(|_| parse_failure(format!("`{number}` is not a {what}")))
+        })
+        .collect()
+}
+
+/// A comma-separated list of `R{row}C{col}` labels, each on the board.
+fn coordinates(list: &str, width: usize, height: usize) -> Result<Vec<(usize, usize)>, BoardError> {
+    list.split(',')
+        .filter(|label| !label.is_empty())
+        .map(|label| {
+            label
+                .strip_prefix('R')
+                .and_then(|label| label.split_once('C'))
+                .and_then(|(row, col)| Some((row.parse().ok()?, col.parse().ok()?)))
+                .filter(|&(row, col)| row < height && col < width)
+                .ok_or_else(|| parse_failure(format!("`{label}` is not a cell on the board")))
+        })
+        .collect()
+}
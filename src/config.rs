@@ -0,0 +1,78 @@
+//! Knobs for how `solve_with_config` goes about solving.
+
+use std::ops::{BitOr, Sub};
+
+/// Which deductions the solver may use, as a set of flags combined with `|`.
+/// Rows and columns cover both shading full lines and starring nearly-empty ones,
+/// and regions likewise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RuleSet(u32);
+
+impl RuleSet {
+    pub const ROWS: RuleSet = RuleSet(1 << 0);
+    pub const COLS: RuleSet = RuleSet(1 << 1);
+    pub const REGIONS: RuleSet = RuleSet(1 << 2);
+    /// Shading the cells around each star.
+    pub const ADJACENCY: RuleSet = RuleSet(1 << 3);
+    /// Shading next to a line's blanks when they all sit together.
+    pub const CONTIGUITY: RuleSet = RuleSet(1 << 4);
+    /// Shading the middle of small empty regions.
+    pub const SMALL_REGION: RuleSet = RuleSet(1 << 5);
+    /// Placing a region's last star once few cells are left for it.
+    pub const LAST_REGION_STAR: RuleSet = RuleSet(1 << 6);
+    /// Shading the rest of a line that a region's blanks are confined to,
+    /// when the region owes the line all its missing stars.
+    pub const CONFINEMENT: RuleSet = RuleSet(1 << 7);
+    /// Placing a star in a cell that every way of seating a unit's missing
+    /// stars has to use.
+    pub const HIDDEN_SINGLE: RuleSet = RuleSet(1 << 8);
+    /// Shading what touches all of a region's blanks once its last star is
+    /// confined to one 2x2 block.
+    pub const BLOCKS: RuleSet = RuleSet(1 << 9);
+    /// Shading the rest of a band of rows or columns that some regions
+    /// between them have to fill with all its missing stars.
+    pub const PIGEONHOLE: RuleSet = RuleSet(1 << 10);
+
+    pub const fn empty() -> RuleSet {
+        RuleSet(0)
+    }
+
+    pub const fn all() -> RuleSet {
+        RuleSet((1 << 11) - 1)
+    }
+
+    /// Whether every rule in `other` is in this set too.
+    pub const fn contains(self, other: RuleSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Every rule is on unless turned off.
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl BitOr for RuleSet {
+    type Output = RuleSet;
+
+    fn bitor(self, other: RuleSet) -> RuleSet {
+        RuleSet(self.0 | other.0)
+    }
+}
+
+/// The rules in `self` that aren't in `other`.
+impl Sub for RuleSet {
+    type Output = RuleSet;
+
+    fn sub(self, other: RuleSet) -> RuleSet {
+        RuleSet(self.0 & !other.0)
+    }
+}
+
+/// How to solve a board. The default is the same as plain `solve`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolveConfig {
+    pub rules: RuleSet,
+}
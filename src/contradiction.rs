@@ -0,0 +1,141 @@
+//! What exactly is wrong with a contradictory board, for tracking down
+//! transcription mistakes in a region map.
+
+use std::fmt::Display;
+
+use crate::{Board, CellState, Unit};
+
+/// The first broken constraint `Board::contradiction` finds, with the cells involved.
+/// Coordinates are `(row, col)` from 0, like everywhere else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Contradiction {
+    /// Two stars touch.
+    StarsTouch((usize, usize), (usize, usize)),
+    /// A unit has more stars than its quota.
+    TooManyStars {
+        unit: Unit,
+        stars: Vec<(usize, usize)>,
+    },
+    /// A unit is missing `need` stars but has fewer blanks than that left.
+    TooFewBlanks {
+        unit: Unit,
+        need: usize,
+        blanks: Vec<(usize, usize)>,
+    },
+    /// A line has enough blanks for the `need` stars it's missing, but they
+    /// touch each other too much to seat them all.
+    BlanksTouch {
+        unit: Unit,
+        need: usize,
+        blanks: Vec<(usize, usize)>,
+    },
+}
+
+impl Contradiction {
+    /// The cells the contradiction is about.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Self::StarsTouch(first, second) => vec![*first, *second],
+            Self::TooManyStars { stars, .. } => stars.clone(),
+            Self::TooFewBlanks { blanks, .. } | Self::BlanksTouch { blanks, .. } => blanks.clone(),
+        }
+    }
+}
+
+impl Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StarsTouch((row1, col1), (row2, col2)) => {
+                write!(f, "stars at ({row1}, {col1}) and ({row2}, {col2}) touch")
+            }
+            Self::TooManyStars { unit, stars } => {
+                write!(f, "{unit} has {} stars: {}", stars.len(), list(stars))
+            }
+            Self::TooFewBlanks { unit, need, blanks } if blanks.is_empty() => {
+                write!(f, "{unit} {} but has no cells left", needs(*need))
+            }
+            Self::TooFewBlanks { unit, need, blanks } => {
+                let remain = if blanks.len() == 1 {
+                    "remains"
+                } else {
+                    "remain"
+                };
+                write!(
+                    f,
+                    "{unit} {} but only {} {remain}",
+                    needs(*need),
+                    list(blanks)
+                )
+            }
+            Self::BlanksTouch { unit, need, blanks } => write!(
+                f,
+                "{unit} {} but only {} remain, and they touch",
+                needs(*need),
+                list(blanks)
+            ),
+        }
+    }
+}
+
+fn needs(need: usize) -> String {
+    match need {
+        1 => "needs 1 more star".to_string(),
+        need => format!("needs {need} more stars"),
+    }
+}
+
+fn list(cells: &[(usize, usize)]) -> String {
+    let cells = cells
+        .iter()
+        .map(|(row, col)| format!("({row}, {col})"))
+        .collect::<Vec<_>>();
+    match &cells[..] {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+impl Board {
+    /// Why the board can't be finished, or `None` if nothing is wrong yet.
+    /// This finds the same problems `solve_with_cancel` reports as a bare `Contradiction`.
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        let stars = self.cells_where(|state| state == CellState::Star);
+        for &star in &stars {
+            let around = self.adjacencies(star.0, star.1);
+            if let Some(&other) = stars.iter().find(|other| around.contains(other)) {
+                return Some(Contradiction::StarsTouch(star, other));
+            }
+        }
+
+        self.units().find_map(|unit| {
+            let cells = self.unit_cells(unit);
+            let of = |state| {
+                cells
+                    .iter()
+                    .copied()
+                    .filter(|&(row, col)| self.cells[row][col].state == state)
+                    .collect::<Vec<_>>()
+            };
+            let (stars, blanks) = (of(CellState::Star), of(CellState::Blank));
+            let quota = self.quota(unit);
+            let need = quota.saturating_sub(stars.len());
+            if stars.len() > quota {
+                Some(Contradiction::TooManyStars { unit, stars })
+            } else if blanks.len() < need {
+                Some(Contradiction::TooFewBlanks { unit, need, blanks })
+            } else if self.line_cannot_seat(unit, (stars.len(), blanks.len())) {
+                Some(Contradiction::BlanksTouch { unit, need, blanks })
+            } else {
+                None
+            }
+        })
+    }
+
+    fn cells_where(&self, keep: impl Fn(CellState) -> bool) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| keep(self.cells[row][col].state))
+            .collect()
+    }
+}
@@ -0,0 +1,79 @@
+//! Grading a puzzle by the hardest deduction it takes to solve.
+
+use std::collections::HashMap;
+
+use crate::{Board, RuleKind, SolveOutcome};
+
+/// How hard a puzzle is, and how often each deduction came up solving it,
+/// as `Board::difficulty` rates it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difficulty {
+    pub rating: Rating,
+    /// How many times each deduction changed the board. Deductions that never
+    /// did are left out.
+    pub rule_counts: HashMap<RuleKind, usize>,
+}
+
+/// The hardest thing a puzzle asks of the solver. Harder ratings sort later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rating {
+    /// Counting stars in rows, columns and regions, and keeping them apart.
+    Easy,
+    /// Working through the ways a unit's stars can be seated.
+    Medium,
+    /// The deductions run dry, so it takes guessing.
+    Hard,
+}
+
+impl RuleKind {
+    /// The rating a puzzle gets for needing this deduction.
+    fn rating(self) -> Rating {
+        match self {
+            Self::BlackoutCol
+            | Self::BlackoutRow
+            | Self::BlackoutRegion
+            | Self::StarAdjacency
+            | Self::RequiredStarCol
+            | Self::RequiredStarRow
+            | Self::RequiredStarRegion
+            | Self::LastRegionStar
+            | Self::Block => Rating::Easy,
+            Self::Contiguity
+            | Self::SmallRegion
+            | Self::Confinement
+            | Self::HiddenSingle
+            | Self::RowPigeonhole
+            | Self::ColPigeonhole => Rating::Medium,
+        }
+    }
+}
+
+impl Board {
+    /// Solves a copy of the board with the deductions, one at a time as
+    /// `explain` applies them, and rates the puzzle by the hardest one it
+    /// needed. A board they can't finish is `Hard`, and its counts cover only
+    /// the deductions made before they ran dry.
+    pub fn difficulty(&self) -> Difficulty {
+        let mut board = Board {
+            verbose: false,
+            ..self.clone()
+        };
+        let mut rule_counts = HashMap::new();
+        while let Some((rule, _reason, _before)) = board.apply_next_rule() {
+            *rule_counts.entry(rule).or_insert(0) += 1;
+        }
+        let rating = if board.outcome() == SolveOutcome::Solved {
+            rule_counts
+                .keys()
+                .map(|rule: &RuleKind| rule.rating())
+                .max()
+                .unwrap_or(Rating::Easy)
+        } else {
+            Rating::Hard
+        };
+        Difficulty {
+            rating,
+            rule_counts,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use crate::{CellState, Divergence, Unit};
+
+/// Failure cases for fallible board operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// A row of the region grid doesn't match the board width.
+    RaggedRow {
+        row: usize,
+        len: usize,
+        width: usize,
+    },
+    /// A region's cells don't form a single orthogonally connected piece.
+    DisconnectedRegion(usize),
+    /// Two stars sit next to each other.
+    StarsTouch((usize, usize), (usize, usize)),
+    /// A row, column, or region holds more stars than it's allowed.
+    Overfilled(Unit),
+    /// The coordinates are off the board.
+    OutOfBounds((usize, usize)),
+    /// The cell already holds a star.
+    CellIsStar((usize, usize)),
+    /// A move expected the cell to hold something other than it does.
+    MoveMismatch {
+        cell: (usize, usize),
+        expected: CellState,
+        found: CellState,
+    },
+    /// No region has this tag.
+    UnknownRegion(usize),
+    /// The grid skips this tag, though it uses higher ones.
+    MissingRegion(usize),
+    /// The region can't fit its stars without some of them touching.
+    RegionTooSmall(usize),
+    /// Two boards that needed the same `(width, height)` don't have it.
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// The board isn't square, so its rows and columns want different numbers of stars.
+    NotSquare { width: usize, height: usize },
+    /// A per-region list doesn't have one entry for each region, or the
+    /// board doesn't have one region for each row.
+    WrongRegionCount { expected: usize, found: usize },
+    /// The rows, columns and regions don't all call for the same number of
+    /// stars in total, so no placement can satisfy them all.
+    InconsistentStarTotals {
+        rows: usize,
+        cols: usize,
+        regions: usize,
+    },
+    /// A flat list of cells doesn't fill the board exactly.
+    WrongCellCount { expected: usize, found: usize },
+    /// The input text could not be parsed into a board.
+    ParseFailure(String),
+    /// The deductions decided a cell differently from the solution they
+    /// were being checked against.
+    Diverged(Divergence),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RaggedRow { row, len, width } => {
+                write!(f, "row {row} has {len} cells but the board is {width} wide")
+            }
+            Self::DisconnectedRegion(region) => write!(f, "region {region} is not connected"),
+            Self::StarsTouch((row1, col1), (row2, col2)) => {
+                write!(f, "stars at ({row1}, {col1}) and ({row2}, {col2}) touch")
+            }
+            Self::Overfilled(unit) => write!(f, "{unit} has too many stars"),
+            Self::OutOfBounds((row, col)) => write!(f, "({row}, {col}) is off the board"),
+            Self::CellIsStar((row, col)) => write!(f, "({row}, {col}) already holds a star"),
+            Self::MoveMismatch {
+                cell: (row, col),
+                expected,
+                found,
+            } => write!(
+                f,
+                "the move expects `{expected}` at ({row}, {col}) but found `{found}`"
+            ),
+            Self::UnknownRegion(region) => write!(f, "there is no region {region}"),
+            Self::MissingRegion(region) => write!(f, "no region is tagged {region}"),
+            Self::RegionTooSmall(region) => {
+                write!(f, "region {region} is too small for its stars")
+            }
+            Self::DimensionMismatch { expected, found } => write!(
+                f,
+                "expected a {}x{} board but found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            Self::NotSquare { width, height } => {
+                write!(f, "the board is {width}x{height} but has to be square")
+            }
+            Self::WrongRegionCount { expected, found } => {
+                write!(f, "expected {expected} regions but found {found}")
+            }
+            Self::InconsistentStarTotals {
+                rows,
+                cols,
+                regions,
+            } => write!(
+                f,
+                "the rows want {rows} stars in all, the columns {cols} and the regions {regions}"
+            ),
+            Self::WrongCellCount { expected, found } => {
+                write!(f, "expected {expected} cells but found {found}")
+            }
+            Self::ParseFailure(reason) => write!(f, "failed to parse board: {reason}"),
+            Self::Diverged(divergence) => divergence.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
@@ -0,0 +1,433 @@
+//! A numbered, plain-English account of a solve, for walkthroughs, a
+//! cell-by-cell trace, step-by-step snapshots or tallies of the same, and a
+//! peek at which deduction comes next.
+
+use std::{collections::HashMap, fmt::Display, sync::atomic::AtomicBool};
+
+use crate::{Board, Cell, CellState, RuleSet, SolveOutcome};
+
+type Rule = fn(&mut Board);
+
+/// The deductions in the order `explain` tries them, each with the reasoning
+/// it stands for.
+const RULES: [(RuleSet, RuleKind, &str, Rule); 15] = [
+    (
+        RuleSet::COLS,
+        RuleKind::BlackoutCol,
+        "A column with all its stars has no room for more",
+        Board::blackout_cols,
+    ),
+    (
+        RuleSet::ROWS,
+        RuleKind::BlackoutRow,
+        "A row with all its stars has no room for more",
+        Board::blackout_rows,
+    ),
+    (
+        RuleSet::REGIONS,
+        RuleKind::BlackoutRegion,
+        "A region with all its stars has no room for more",
+        Board::blackout_regions,
+    ),
+    (
+        RuleSet::ADJACENCY,
+        RuleKind::StarAdjacency,
+        "Stars can't touch, not even diagonally",
+        Board::blackout_star_adjacencies,
+    ),
+    (
+        RuleSet::CONTIGUITY,
+        RuleKind::Contiguity,
+        "However a line's remaining stars are seated, they touch these cells in the lines beside it",
+        Board::blackout_next_to_contiguity,
+    ),
+    (
+        RuleSet::SMALL_REGION,
+        RuleKind::SmallRegion,
+        "A small empty region has only a few ways to fit its stars, and they all rule these cells out",
+        Board::eliminate_middle_of_small_empty_regions,
+    ),
+    (
+        RuleSet::CONFINEMENT,
+        RuleKind::Confinement,
+        "A region's remaining stars all have to go in one line, which has no room for any others",
+        Board::blackout_beside_confined_regions,
+    ),
+    (
+        RuleSet::BLOCKS,
+        RuleKind::Block,
+        "A region's last star has to go in one 2x2 block, and these cells touch all of it",
+        Board::blackout_full_2x2_blocks,
+    ),
+    (
+        RuleSet::PIGEONHOLE,
+        RuleKind::RowPigeonhole,
+        "Some regions inside a band of rows owe it all the stars it's missing",
+        Board::blackout_region_row_pigeonhole,
+    ),
+    (
+        RuleSet::PIGEONHOLE,
+        RuleKind::ColPigeonhole,
+        "Some regions inside a band of columns owe it all the stars it's missing",
+        Board::blackout_region_col_pigeonhole,
+    ),
+    (
+        RuleSet::COLS,
+        RuleKind::RequiredStarCol,
+        "A column has just enough room left for its stars",
+        Board::add_required_stars_cols,
+    ),
+    (
+        RuleSet::ROWS,
+        RuleKind::RequiredStarRow,
+        "A row has just enough room left for its stars",
+        Board::add_required_stars_rows,
+    ),
+    (
+        RuleSet::REGIONS,
+        RuleKind::RequiredStarRegion,
+        "A region has just enough room left for its stars",
+        Board::add_required_stars_region,
+    ),
+    (
+        RuleSet::HIDDEN_SINGLE,
+        RuleKind::HiddenSingle,
+        "Every way of seating a unit's remaining stars uses this cell",
+        Board::add_hidden_singles,
+    ),
+    (
+        RuleSet::LAST_REGION_STAR,
+        RuleKind::LastRegionStar,
+        "A region's last star is down to its final few cells",
+        Board::add_last_region_star,
+    ),
+];
+
+/// One of the deductions the solver makes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    /// A column with all its stars has the rest of it shaded.
+    BlackoutCol,
+    /// A row with all its stars has the rest of it shaded.
+    BlackoutRow,
+    /// A region with all its stars has the rest of it shaded.
+    BlackoutRegion,
+    /// The cells around a star are shaded.
+    StarAdjacency,
+    /// Cells beside a line that every seating of its stars touches are shaded.
+    Contiguity,
+    /// Cells a small empty region's seatings rule out are shaded.
+    SmallRegion,
+    /// A line a region's stars are confined to has the rest of it shaded.
+    Confinement,
+    /// Cells touching every blank of a region whose last star is confined to
+    /// a 2x2 block are shaded.
+    Block,
+    /// A band of rows whose stars some regions inside it have to supply has
+    /// the rest of it shaded.
+    RowPigeonhole,
+    /// The same, for a band of columns.
+    ColPigeonhole,
+    /// A column with just enough room left has its stars placed.
+    RequiredStarCol,
+    /// A row with just enough room left has its stars placed.
+    RequiredStarRow,
+    /// A region with just enough room left has its stars placed.
+    RequiredStarRegion,
+    /// A cell every seating of a unit's stars uses gets a star.
+    HiddenSingle,
+    /// A region's last star goes in its last few cells.
+    LastRegionStar,
+}
+
+impl RuleKind {
+    /// The name of the method behind the deduction, as `peek_next_rule` gives it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BlackoutCol => "blackout_cols",
+            Self::BlackoutRow => "blackout_rows",
+            Self::BlackoutRegion => "blackout_regions",
+            Self::StarAdjacency => "blackout_star_adjacencies",
+            Self::Contiguity => "blackout_next_to_contiguity",
+            Self::SmallRegion => "eliminate_middle_of_small_empty_regions",
+            Self::Confinement => "blackout_beside_confined_regions",
+            Self::Block => "blackout_full_2x2_blocks",
+            Self::RowPigeonhole => "blackout_region_row_pigeonhole",
+            Self::ColPigeonhole => "blackout_region_col_pigeonhole",
+            Self::RequiredStarCol => "add_required_stars_cols",
+            Self::RequiredStarRow => "add_required_stars_rows",
+            Self::RequiredStarRegion => "add_required_stars_region",
+            Self::HiddenSingle => "add_hidden_singles",
+            Self::LastRegionStar => "add_last_region_star",
+        }
+    }
+}
+
+impl Display for RuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// One cell changed by a deduction, as `solve_with_trace` records it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deduction {
+    pub row: usize,
+    pub col: usize,
+    /// what the cell became: a star, or shaded
+    pub new_state: CellState,
+    /// the deduction that changed it
+    pub rule: RuleKind,
+}
+
+/// Tallies from `solve_with_stats`: how much each deduction did, without
+/// saying which cells.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// passes of the deduction loop, as `SolveReport::outer_passes` counts them
+    pub rounds: usize,
+    /// how many times each kind of deduction changed a cell when it ran;
+    /// kinds that never changed anything are left out
+    pub per_rule: HashMap<RuleKind, usize>,
+    pub cells_shaded: usize,
+    pub stars_placed: usize,
+}
+
+/// What a board counts for `solve_with_stats` while it runs.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tally {
+    pub(crate) stats: SolveStats,
+    /// cells decided by the deduction running now, leaving out those of any
+    /// deductions it set off
+    pub(crate) decided: usize,
+}
+
+/// A cell that can be decided next, as `hint` suggests it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hint {
+    pub row: usize,
+    pub col: usize,
+    /// what the cell has to be: a star, or shaded
+    pub state: CellState,
+    /// the deduction that decides it
+    pub reason: RuleKind,
+}
+
+impl Board {
+    /// Solves the board and describes how, one numbered step per line:
+    /// which deduction applied and which cells it starred or shaded. Where the
+    /// deductions run dry the solve takes a guess that's known to work out,
+    /// and says so. The same puzzle always gets the same account.
+    pub fn explain(&mut self) -> String {
+        let mut steps = vec![];
+        loop {
+            if self.is_contradictory() {
+                steps.push("The board contradicts itself, so there's no solution.".to_string());
+                break;
+            }
+            if let Some(step) = self.explain_next_rule() {
+                steps.push(step);
+                continue;
+            }
+            if self.outcome() == SolveOutcome::Solved {
+                steps.push("Every star is in place, so the puzzle is solved.".to_string());
+                break;
+            }
+            let Some(solution) = self.find_solution() else {
+                steps.push(
+                    "No further logic available, and no guess leads to a solution.".to_string(),
+                );
+                break;
+            };
+            let (row, col) = self
+                .most_constrained_blank()
+                .or_else(|| self.first_blank_anywhere())
+                .expect("a stuck board has a blank");
+            if solution.cells[row][col].state == CellState::Star {
+                self.star_cell(row, col);
+                steps.push(format!(
+                    "No further logic available; trying {} as a star.",
+                    label(row, col)
+                ));
+            } else {
+                self.shade_cell(row, col);
+                steps.push(format!(
+                    "No further logic available; trying {} empty.",
+                    label(row, col)
+                ));
+            }
+            self.regenerate_regions();
+        }
+        steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| format!("Step {}: {step}\n", index + 1))
+            .collect()
+    }
+
+    /// Applies the deductions one at a time, as `explain` does, and records
+    /// every cell each of them starred or shaded, in the order they did it.
+    /// A star deduction also runs the shading rules it sets off, and the cells
+    /// those shade are put down to it. Nothing is guessed.
+    pub fn solve_with_trace(&mut self) -> Vec<Deduction> {
+        let mut trace = vec![];
+        while let Some((rule, _reason, before)) = self.apply_next_rule() {
+            for (row, cells) in self.cells.iter().enumerate() {
+                for (col, cell) in cells.iter().enumerate() {
+                    if before[row][col].state != cell.state {
+                        trace.push(Deduction {
+                            row,
+                            col,
+                            new_state: cell.state,
+                            rule,
+                        });
+                    }
+                }
+            }
+        }
+        trace
+    }
+
+    /// Runs the deductions the way `solve` does, and counts what each of
+    /// them did. Nothing is guessed.
+    pub fn solve_with_stats(&mut self) -> SolveStats {
+        self.tally = Some(Box::default());
+        self.deduce(&AtomicBool::new(false));
+        let mut stats = self
+            .tally
+            .take()
+            .map(|tally| tally.stats)
+            .unwrap_or_default();
+        stats.rounds = self.report.outer_passes;
+        stats
+    }
+
+    /// Counts a cell a deduction just decided, if `solve_with_stats` is counting.
+    pub(crate) fn count_decided(&mut self, state: CellState) {
+        let Some(tally) = &mut self.tally else {
+            return;
+        };
+        tally.decided += 1;
+        match state {
+            CellState::Star => tally.stats.stars_placed += 1,
+            CellState::Filled => tally.stats.cells_shaded += 1,
+            CellState::Blank => {}
+        }
+    }
+
+    /// Applies one deduction per call to `next`, the same ones in the same
+    /// order as `explain`, and yields a copy of the board after each. It runs
+    /// out where the deductions do; nothing is guessed.
+    pub fn steps(&mut self) -> impl Iterator<Item = Board> + '_ {
+        std::iter::from_fn(move || {
+            self.apply_next_rule()?;
+            Some(self.clone())
+        })
+    }
+
+    /// One cell the next deduction would decide, for a hint button. The
+    /// deduction runs on a copy, so the board itself is left as is. `None` once
+    /// the deductions have nothing left to add.
+    pub fn hint(&self) -> Option<Hint> {
+        let mut board = Board {
+            verbose: false,
+            ..self.clone()
+        };
+        let (rule, _reason, before) = board.apply_next_rule()?;
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .find(|&(row, col)| board.cells[row][col].state != before[row][col].state)
+            .map(|(row, col)| Hint {
+                row,
+                col,
+                state: board.cells[row][col].state,
+                reason: rule,
+            })
+    }
+
+    /// The name of the deduction `explain` would apply next, if any would
+    /// change the board, and `None` for a board that already breaks the rules,
+    /// as `explain` would leave it. Only a copy of the board is touched.
+    pub fn peek_next_rule(&self) -> Option<&'static str> {
+        let mut board = Board {
+            verbose: false,
+            ..self.clone()
+        };
+        board.apply_next_rule().map(|(kind, ..)| kind.name())
+    }
+
+    /// Applies the first deduction that changes anything and describes what it did.
+    fn explain_next_rule(&mut self) -> Option<String> {
+        let (_kind, reason, before) = self.apply_next_rule()?;
+        Some(self.describe_changes(reason, &before))
+    }
+
+    /// Applies the first deduction that changes anything, returning which it
+    /// was and its reasoning along with the cells as they were beforehand.
+    /// A board that already breaks the rules is left alone.
+    pub(crate) fn apply_next_rule(&mut self) -> Option<(RuleKind, &'static str, Vec<Vec<Cell>>)> {
+        if !self.is_legal() {
+            return None;
+        }
+        self.forget_moves();
+        for (rule, kind, reason, apply) in RULES {
+            if !self.rules.contains(rule) {
+                continue;
+            }
+            let before = self.cells.clone();
+            apply(self);
+            self.regenerate_regions();
+            if self.cells != before {
+                return Some((kind, reason, before));
+            }
+        }
+        None
+    }
+
+    fn describe_changes(&self, reason: &str, before: &[Vec<Cell>]) -> String {
+        let mut stars = vec![];
+        let mut shaded = vec![];
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if before[row][col].state == cell.state {
+                    continue;
+                }
+                match cell.state {
+                    CellState::Star => stars.push(label(row, col)),
+                    CellState::Filled => shaded.push(label(row, col)),
+                    CellState::Blank => {}
+                }
+            }
+        }
+
+        let mut step = reason.to_string();
+        if !stars.is_empty() {
+            let verb = if stars.len() == 1 {
+                "becomes a star"
+            } else {
+                "become stars"
+            };
+            step.push_str(&format!(", so {} {verb}", list(&stars)));
+        }
+        if !shaded.is_empty() {
+            let joint = if stars.is_empty() { ", so" } else { ", and" };
+            let verb = if shaded.len() == 1 { "is" } else { "are" };
+            step.push_str(&format!("{joint} {} {verb} empty", list(&shaded)));
+        }
+        step.push('.');
+        step
+    }
+}
+
+pub(crate) fn label(row: usize, col: usize) -> String {
+    format!("R{}C{}", row + 1, col + 1)
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
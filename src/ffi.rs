@@ -0,0 +1,101 @@
+//! A C interface, behind the `ffi` feature. Boards are handed out as opaque
+//! pointers that must go back to `tnt_board_free`. Nothing panics across the
+//! boundary: failures come back as null pointers or negative codes.
+//!
+//! The crate builds as a plain Rust library; ask for the shared library with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{Board, CellState, SolveOutcome};
+
+/// Returned by `tnt_solve` when the board pointer is null or solving panicked.
+pub const TNT_ERROR: i32 = -1;
+
+/// Builds a board from `width * height` region tags listed row by row.
+/// Returns null if the regions don't make a valid board.
+///
+/// # Safety
+///
+/// `regions` must point to `width * height` readable `size_t`s.
+#[no_mangle]
+pub unsafe extern "C" fn tnt_board_from_flat(
+    width: usize,
+    height: usize,
+    regions: *const usize,
+) -> *mut Board {
+    if regions.is_null() {
+        return ptr::null_mut();
+    }
+    let Some(len) = width.checked_mul(height) else {
+        return ptr::null_mut();
+    };
+    let regions = std::slice::from_raw_parts(regions, len);
+    match catch_unwind(|| Board::from_flat(width, regions)) {
+        Ok(Ok(board)) => Box::into_raw(Box::new(board)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Runs the deductions and reports where they got to: 0 solved, 1 stuck,
+/// 2 contradictory, or `TNT_ERROR`.
+///
+/// # Safety
+///
+/// `board` must be null or a live pointer from `tnt_board_from_flat`.
+#[no_mangle]
+pub unsafe extern "C" fn tnt_solve(board: *mut Board) -> i32 {
+    let Some(board) = board.as_mut() else {
+        return TNT_ERROR;
+    };
+    let outcome = catch_unwind(AssertUnwindSafe(|| board.solve()));
+    match outcome {
+        Ok(SolveOutcome::Solved) => 0,
+        Ok(SolveOutcome::Stuck) => 1,
+        Ok(SolveOutcome::Contradiction) => 2,
+        Ok(SolveOutcome::Cancelled) | Err(_) => TNT_ERROR,
+    }
+}
+
+/// Writes the star positions as `row * width + col`, in row-major order, into
+/// `out`, stopping after `cap` of them. Returns how many stars there are in all,
+/// so a return above `cap` means `out` was too small. A null board has no stars.
+///
+/// # Safety
+///
+/// `board` must be null or a live pointer from `tnt_board_from_flat`, and `out`
+/// must have room for `cap` `size_t`s.
+#[no_mangle]
+pub unsafe extern "C" fn tnt_get_stars(board: *const Board, out: *mut usize, cap: usize) -> usize {
+    let Some(board) = board.as_ref() else {
+        return 0;
+    };
+    let stars = board
+        .cells
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_index, cell)| cell.state == CellState::Star)
+        .map(|(index, _cell)| index)
+        .collect::<Vec<_>>();
+    if !out.is_null() {
+        for (slot, &star) in stars.iter().take(cap).enumerate() {
+            out.add(slot).write(star);
+        }
+    }
+    stars.len()
+}
+
+/// Frees a board from `tnt_board_from_flat`. Null is ignored.
+///
+/// # Safety
+///
+/// `board` must be null or a live pointer from `tnt_board_from_flat`, which
+/// mustn't be used again.
+#[no_mangle]
+pub unsafe extern "C" fn tnt_board_free(board: *mut Board) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
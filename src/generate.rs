@@ -0,0 +1,140 @@
+//! Making new puzzles: a random star layout, regions grown around it, and a
+//! solver check that the stars are the only answer.
+
+use rand::{
+    seq::{IndexedRandom, SliceRandom},
+    Rng,
+};
+
+use crate::Board;
+
+/// How many region layouts to try around one star layout before starting over.
+const LAYOUTS_PER_SOLUTION: usize = 50;
+
+impl Board {
+    /// A blank two-star puzzle with exactly one solution. Everything random
+    /// comes from `rng`, so the same seed gives the same puzzle.
+    ///
+    /// # Panics
+    ///
+    /// If the board isn't square, or is too small to hold two stars per row
+    /// without any touching.
+    pub fn generate(width: usize, height: usize, rng: &mut impl Rng) -> Board {
+        assert_eq!(width, height, "only square puzzles can be generated");
+        loop {
+            let stars =
+                random_stars(width, rng).expect("the board is too small for two stars a row");
+            for _ in 0..LAYOUTS_PER_SOLUTION {
+                let Some(regions) = grow_regions(width, &stars, rng) else {
+                    continue;
+                };
+                let Ok(board) = Board::try_from(regions) else {
+                    continue;
+                };
+                if board.has_unique_solution() {
+                    return board;
+                }
+            }
+        }
+    }
+}
+
+/// Two stars in every row and column, none touching, as each row's columns.
+fn random_stars(size: usize, rng: &mut impl Rng) -> Option<Vec<[usize; 2]>> {
+    let mut rows = vec![];
+    place_row(size, &mut rows, &mut vec![0; size], rng).then_some(rows)
+}
+
+fn place_row(
+    size: usize,
+    rows: &mut Vec<[usize; 2]>,
+    col_counts: &mut [usize],
+    rng: &mut impl Rng,
+) -> bool {
+    if rows.len() == size {
+        return true;
+    }
+    let mut pairs = (0..size)
+        .flat_map(|first| (first + 2..size).map(move |second| [first, second]))
+        .filter(|pair| pair.iter().all(|&col| col_counts[col] < 2))
+        .filter(|pair| {
+            rows.last().is_none_or(|above: &[usize; 2]| {
+                pair.iter()
+                    .all(|&col| above.iter().all(|&star| star.abs_diff(col) > 1))
+            })
+        })
+        .collect::<Vec<_>>();
+    pairs.shuffle(rng);
+    for pair in pairs {
+        for col in pair {
+            col_counts[col] += 1;
+        }
+        rows.push(pair);
+        if place_row(size, rows, col_counts, rng) {
+            return true;
+        }
+        rows.pop();
+        for col in pair {
+            col_counts[col] -= 1;
+        }
+    }
+    false
+}
+
+/// One region per row, each grown from a star until it has picked up exactly
+/// one more, as region tags row by row. `None` if the growth boxes itself in.
+fn grow_regions(size: usize, stars: &[[usize; 2]], rng: &mut impl Rng) -> Option<Vec<Vec<usize>>> {
+    let is_star = |(row, col): (usize, usize)| stars[row].contains(&col);
+    let mut cells = stars
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cols)| cols.iter().map(move |&col| (row, col)))
+        .collect::<Vec<_>>();
+    cells.shuffle(rng);
+
+    let mut tags = vec![vec![None; size]; size];
+    let mut star_counts = vec![1; size];
+    for (tag, &(row, col)) in cells.iter().take(size).enumerate() {
+        tags[row][col] = Some(tag);
+    }
+    let mut unassigned = size * size - size;
+    while unassigned > 0 {
+        let mut moves = vec![];
+        for row in 0..size {
+            for col in 0..size {
+                let Some(tag) = tags[row][col] else {
+                    continue;
+                };
+                let neighbours = [
+                    row.checked_sub(1).map(|row| (row, col)),
+                    (row + 1 < size).then_some((row + 1, col)),
+                    col.checked_sub(1).map(|col| (row, col)),
+                    (col + 1 < size).then_some((row, col + 1)),
+                ];
+                for (row, col) in neighbours.into_iter().flatten() {
+                    if tags[row][col].is_none() && (!is_star((row, col)) || star_counts[tag] < 2) {
+                        moves.push((tag, row, col));
+                    }
+                }
+            }
+        }
+        let &(tag, row, col) = moves.choose(rng)?;
+        tags[row][col] = Some(tag);
+        if is_star((row, col)) {
+            star_counts[tag] += 1;
+        }
+        unassigned -= 1;
+    }
+    if star_counts.iter().any(|&count| count != 2) {
+        return None;
+    }
+    Some(
+        tags.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|tag| tag.expect("every cell was assigned"))
+                    .collect()
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,92 @@
+//! Undo and redo for boards being played by hand.
+
+use crate::{Board, BoardError, CellState};
+
+/// One cell changing state, as a player makes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub row: usize,
+    pub col: usize,
+    /// what the cell has to hold for the move to apply
+    pub from: CellState,
+    /// what the move leaves in it
+    pub to: CellState,
+}
+
+impl Move {
+    /// The move that takes it back.
+    fn inverse(self) -> Move {
+        Move {
+            from: self.to,
+            to: self.from,
+            ..self
+        }
+    }
+}
+
+/// The moves a board can undo, most recent last, and the undone ones it can
+/// redo, most recently undone last.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MoveHistory {
+    undo: Vec<Move>,
+    redo: Vec<Move>,
+}
+
+impl Board {
+    /// Makes `mv`, remembering it for `undo`. Anything undone before is
+    /// forgotten, as in any editor. The cell has to be on the board and hold
+    /// `mv.from`, so that undoing puts back exactly what was there.
+    pub fn apply_move(&mut self, mv: Move) -> Result<(), BoardError> {
+        self.set_cell(mv)?;
+        self.history.undo.push(mv);
+        self.history.redo.clear();
+        Ok(())
+    }
+
+    /// Takes back the most recent move, and returns it, or `None` if there's
+    /// nothing to undo. If the cell has changed since some other way, this is
+    /// a `MoveMismatch` and the move stays on the list.
+    pub fn undo(&mut self) -> Result<Option<Move>, BoardError> {
+        let Some(&mv) = self.history.undo.last() else {
+            return Ok(None);
+        };
+        self.set_cell(mv.inverse())?;
+        self.history.undo.pop();
+        self.history.redo.push(mv);
+        Ok(Some(mv))
+    }
+
+    /// Makes the most recently undone move again, and returns it, or `None`
+    /// if there's nothing to redo. Fails like `undo` does.
+    pub fn redo(&mut self) -> Result<Option<Move>, BoardError> {
+        let Some(&mv) = self.history.redo.last() else {
+            return Ok(None);
+        };
+        self.set_cell(mv)?;
+        self.history.redo.pop();
+        self.history.undo.push(mv);
+        Ok(Some(mv))
+    }
+
+    /// Forgets every move, for when the solver has been over the board and
+    /// the moves no longer describe how it got there.
+    pub(crate) fn forget_moves(&mut self) {
+        self.history = MoveHistory::default();
+    }
+
+    fn set_cell(&mut self, mv: Move) -> Result<(), BoardError> {
+        let cell = self
+            .cell(mv.row, mv.col)
+            .ok_or(BoardError::OutOfBounds((mv.row, mv.col)))?;
+        if cell.state != mv.from {
+            return Err(BoardError::MoveMismatch {
+                cell: (mv.row, mv.col),
+                expected: mv.from,
+                found: cell.state,
+            });
+        }
+        self.set_state(mv.row, mv.col, mv.to);
+        self.reindex_regions();
+        Ok(())
+    }
+}
@@ -1,84 +1,452 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg(feature = "rayon")]
+mod batch;
+mod bitboard;
+mod bordered;
+mod compact;
+mod config;
+mod contradiction;
+mod difficulty;
+mod error;
+mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "generate")]
+mod generate;
+mod history;
+mod parse;
+mod sbn;
+mod search;
+#[cfg(feature = "serde")]
+mod serialize;
+mod svg;
+mod verify;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "rayon")]
+pub use batch::solve_all;
+use bitboard::BitBoard;
+pub use config::{RuleSet, SolveConfig};
+pub use contradiction::Contradiction;
+pub use difficulty::{Difficulty, Rating};
+pub use error::BoardError;
+use explain::Tally;
+pub use explain::{Deduction, Hint, RuleKind, SolveStats};
+pub use history::Move;
+use history::MoveHistory;
+pub use search::{BranchCandidate, Mrv, SearchHeuristic};
+pub use svg::SvgOptions;
+pub use verify::Divergence;
+#[cfg(feature = "wasm")]
+pub use wasm::{solve_from_grid, WasmBoard};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "serialize::StoredBoard", try_from = "serialize::StoredBoard")
+)]
 pub struct Board {
     width: usize,
     height: usize,
     cells: Vec<Vec<Cell>>,
-    /// indexable by region tag
+    /// The cells of each region that aren't shaded yet, indexed by region tag.
+    /// There's an entry for every tag up to the largest, even ones no cell uses,
+    /// and `regenerate_regions` only ever empties entries, so an index keeps
+    /// meaning the same region for the life of the board.
     regions: Vec<Vec<(usize, usize)>>,
+    /// whether the edges wrap around, making the board a torus
+    wrap: bool,
+    /// which cells count as touching a star
+    adjacency: AdjacencyMode,
+    /// how many stars every row and column holds
+    stars_per_unit: usize,
+    /// how many stars each region holds, indexed like `regions`
+    region_star_counts: Vec<usize>,
+    /// pass counts from the most recent `solve`
+    report: SolveReport,
+    /// whether solving prints the board as it goes, which only `set_verbose` turns on
+    verbose: bool,
+    /// the deductions `solve` may use
+    rules: RuleSet,
+    /// set whenever a rule stars or shades a blank, so the deduction loops can
+    /// tell when a pass has left the board as it was
+    changed: bool,
+    /// moves made with `apply_move`, for `undo` and `redo`
+    history: MoveHistory,
+    /// what `solve_with_stats` has counted so far, kept only while it runs
+    tally: Option<Box<Tally>>,
     #[cfg(test)]
     solution: Option<Box<Board>>,
 }
 
+/// The pass counters, solver flags and move history only describe how the
+/// board got here, so they're left out, and so is the region index, which
+/// always follows from the cells. Boards played by different rules differ
+/// even with the same cells; `same_cells` is for when that doesn't matter.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_cells(other)
+            && self.wrap == other.wrap
+            && self.adjacency == other.adjacency
+            && self.stars_per_unit == other.stars_per_unit
+            && self.region_star_counts == other.region_star_counts
+    }
+}
+
+impl Eq for Board {}
+
+/// Hashes the same fields `eq` compares, so equal boards hash alike.
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.cells.hash(state);
+        self.wrap.hash(state);
+        self.adjacency.hash(state);
+        self.stars_per_unit.hash(state);
+        self.region_star_counts.hash(state);
+    }
+}
+
+/// One line per row: stars and shading as their glyphs, blanks as their region tag.
+/// Every cell is padded to the width of the longest tag, so columns line up
+/// on boards with more than ten regions.
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(
+            f,
+            &GlyphSet {
+                blank: None,
+                ..GlyphSet::default()
+            },
+        )
+    }
+}
+
+/// Takes the board's size from the grid, which must be square, with every
+/// region in one orthogonally connected piece and as many regions as rows.
+/// Anything else leaves the rows, columns and regions wanting different
+/// numbers of stars in all, so it couldn't be solved.
+impl TryFrom<Vec<Vec<usize>>> for Board {
+    type Error = BoardError;
+
+    fn try_from(regions: Vec<Vec<usize>>) -> Result<Self, Self::Error> {
+        let height = regions.len();
+        let width = regions.first().map_or(0, Vec::len);
+        if let Some((row, cells)) = regions
+            .iter()
+            .enumerate()
+            .find(|(_row, cells)| cells.len() != width)
+        {
+            return Err(BoardError::RaggedRow {
+                row,
+                len: cells.len(),
+                width,
+            });
+        }
+        if width != height {
+            return Err(BoardError::NotSquare { width, height });
+        }
+        let board = Self::build(width, height, regions);
+        if let Err(split) = board.validate_regions() {
+            return Err(BoardError::DisconnectedRegion(split[0]));
+        }
+        if board.num_regions() != height {
+            return Err(BoardError::WrongRegionCount {
+                expected: height,
+                found: board.num_regions(),
+            });
+        }
+        Ok(board)
+    }
+}
+
 impl Board {
+    /// # Panics
+    ///
+    /// If `regions` isn't a valid `width` by `height` grid; see `Board::try_new`.
     pub fn new(width: usize, height: usize, regions: Vec<Vec<usize>>) -> Self {
-        let cells = Self::blank_from_regions(regions);
-        let cells_by_region = cells
+        Self::with_stars_per_unit(width, height, regions, 2)
+    }
+
+    /// `new`, for variants with `stars_per_unit` stars in every row, column and region
+    /// rather than two.
+    ///
+    /// # Panics
+    ///
+    /// If `regions` isn't a valid `width` by `height` grid; see `Board::try_new`.
+    pub fn with_stars_per_unit(
+        width: usize,
+        height: usize,
+        regions: Vec<Vec<usize>>,
+        stars_per_unit: usize,
+    ) -> Self {
+        Self::try_with_stars_per_unit(width, height, regions, stars_per_unit)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// `new`, for grids that haven't been checked yet. On top of everything
+    /// `Board::try_from` checks, the grid has to be `width` by `height`, its
+    /// tags have to run from 0 with none skipped, and every region has to have
+    /// room for two stars that don't touch.
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        regions: Vec<Vec<usize>>,
+    ) -> Result<Board, BoardError> {
+        Self::try_with_stars_per_unit(width, height, regions, 2)
+    }
+
+    fn try_with_stars_per_unit(
+        width: usize,
+        height: usize,
+        regions: Vec<Vec<usize>>,
+        stars_per_unit: usize,
+    ) -> Result<Board, BoardError> {
+        let mut board = Self::try_from(regions)?;
+        if (board.width, board.height) != (width, height) {
+            return Err(BoardError::DimensionMismatch {
+                expected: (width, height),
+                found: (board.width, board.height),
+            });
+        }
+        if let Some(tag) = (0..board.regions.len()).find(|&tag| board.regions[tag].is_empty()) {
+            return Err(BoardError::MissingRegion(tag));
+        }
+        board.set_stars_per_unit(stars_per_unit);
+        board.validate_star_totals()?;
+        if let Some(tag) = (0..board.regions.len())
+            .find(|&tag| !board.has_seating(&board.regions[tag], board.region_star_counts[tag]))
+        {
+            return Err(BoardError::RegionTooSmall(tag));
+        }
+        Ok(board)
+    }
+
+    /// A blank `width` by `height` board that's all one region, for trying out
+    /// rows and columns without regions getting in the way. The region is
+    /// owed every star the rows are, so it never forces anything itself, and
+    /// `set_stars_per_unit` keeps it that way. Only the row, column, adjacency
+    /// and contiguity deductions are on, since the rest would spend forever
+    /// seating that many stars in one region. `to_compact` and serde store it
+    /// like any other board and bring it back as this.
+    pub fn empty(width: usize, height: usize) -> Board {
+        let mut board = Self::build(width, height, vec![vec![0; width]; height]);
+        if let Some(count) = board.region_star_counts.first_mut() {
+            *count = board.stars_per_unit * height;
+        }
+        board.rules = RuleSet::ROWS | RuleSet::COLS | RuleSet::ADJACENCY | RuleSet::CONTIGUITY;
+        board
+    }
+
+    /// `Board::try_from` for region maps read back by `from_compact` and serde,
+    /// which may also be the single region `Board::empty` makes.
+    pub(crate) fn from_stored_regions(regions: Vec<Vec<usize>>) -> Result<Board, BoardError> {
+        let width = regions.first().map_or(0, Vec::len);
+        let all_one = regions
             .iter()
-            .enumerate()
-            .flat_map(|(row_index, row)| {
-                row.iter()
-                    .enumerate()
-                    .map(move |(col_index, cell)| (cell.region, (row_index, col_index)))
-            })
-            .collect::<Vec<_>>();
+            .all(|row| row.len() == width && row.iter().all(|&tag| tag == 0));
+        if width > 0 && all_one {
+            return Ok(Self::empty(width, regions.len()));
+        }
+        Self::try_from(regions)
+    }
+
+    /// Builds a board from its region tags listed row by row, as many datasets
+    /// store them, `width` to a row. Validated like `Board::try_from`.
+    pub fn from_flat(width: usize, regions: &[usize]) -> Result<Board, BoardError> {
+        if width == 0 {
+            return match regions.len() {
+                0 => Self::try_from(vec![]),
+                found => Err(BoardError::WrongCellCount { expected: 0, found }),
+            };
+        }
+        if !regions.len().is_multiple_of(width) {
+            return Err(BoardError::WrongCellCount {
+                expected: regions.len().next_multiple_of(width),
+                found: regions.len(),
+            });
+        }
+        Self::try_from(
+            regions
+                .chunks(width)
+                .map(<[usize]>::to_vec)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Builds a board like `Board::try_from`, with stars already in the cells
+    /// listed in `givens`, as puzzles that reveal a few stars come. The solver
+    /// works from them like stars it placed itself. Givens that touch, or that
+    /// overfill a row, column or region, are rejected.
+    pub fn with_givens(
+        width: usize,
+        height: usize,
+        regions: Vec<Vec<usize>>,
+        givens: &[(usize, usize)],
+    ) -> Result<Board, BoardError> {
+        let mut board = Self::try_from(regions)?;
+        if (board.width, board.height) != (width, height) {
+            return Err(BoardError::DimensionMismatch {
+                expected: (width, height),
+                found: (board.width, board.height),
+            });
+        }
+        for &(row, col) in givens {
+            if row >= height || col >= width {
+                return Err(BoardError::OutOfBounds((row, col)));
+            }
+            board.star_cell(row, col);
+        }
+        for &(row, col) in givens {
+            if let Some(other) = board
+                .adjacencies(row, col)
+                .into_iter()
+                .find(|&(row, col)| board.cells[row][col].state == CellState::Star)
+            {
+                return Err(BoardError::StarsTouch((row, col), other));
+            }
+        }
+        if let Some(unit) = board
+            .units()
+            .find(|&unit| board.unit_counts(unit).0 > board.quota(unit))
+        {
+            return Err(BoardError::Overfilled(unit));
+        }
+        Ok(board)
+    }
 
-        let mut regional_map: HashMap<usize, Vec<_>> = HashMap::new();
-        for (region, coords) in cells_by_region {
-            regional_map.entry(region).or_default().push(coords);
+    /// Checks that every region is one orthogonally connected piece, as
+    /// `Board::try_from` insists. Boards whose cells were retagged afterwards
+    /// can fail, and then this lists the regions in pieces, in the order a
+    /// scan down the rows first comes across a second piece of each.
+    pub fn validate_regions(&self) -> Result<(), Vec<usize>> {
+        let mut seen = vec![vec![false; self.width]; self.height];
+        let mut pieces = HashMap::new();
+        let mut split = vec![];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if seen[row][col] {
+                    continue;
+                }
+                let region = self.cells[row][col].region;
+                *pieces.entry(region).or_insert(0) += 1;
+                if pieces[&region] == 2 {
+                    split.push(region);
+                }
+                seen[row][col] = true;
+                let mut frontier = vec![(row, col)];
+                while let Some((row, col)) = frontier.pop() {
+                    let neighbours = [
+                        row.checked_sub(1).map(|row| (row, col)),
+                        (row + 1 < self.height).then_some((row + 1, col)),
+                        col.checked_sub(1).map(|col| (row, col)),
+                        (col + 1 < self.width).then_some((row, col + 1)),
+                    ];
+                    for (row, col) in neighbours.into_iter().flatten() {
+                        if !seen[row][col] && self.cells[row][col].region == region {
+                            seen[row][col] = true;
+                            frontier.push((row, col));
+                        }
+                    }
+                }
+            }
+        }
+        if split.is_empty() {
+            Ok(())
+        } else {
+            Err(split)
+        }
+    }
+
+    /// `new` without printing the fresh board.
+    fn build(width: usize, height: usize, regions: Vec<Vec<usize>>) -> Self {
+        let cells = Self::blank_from_regions(regions);
+        let tags = cells.iter().flatten().map(|cell| cell.region).max();
+        let mut regions = vec![vec![]; tags.map_or(0, |tag| tag + 1)];
+        for (row_index, row) in cells.iter().enumerate() {
+            for (col_index, cell) in row.iter().enumerate() {
+                regions[cell.region].push((row_index, col_index));
+            }
         }
-        let mut tagged_regions = regional_map.into_iter().collect::<Vec<_>>();
-        tagged_regions.sort();
 
-        let result = Self {
+        // tags the grid skips over are regions with no cells, which need no stars
+        let region_star_counts = regions
+            .iter()
+            .map(|region| if region.is_empty() { 0 } else { 2 })
+            .collect();
+        Self {
             width,
             height,
             cells,
-            regions: tagged_regions
-                .into_iter()
-                .map(|(_region, cells)| cells)
-                .collect(),
+            regions,
+            wrap: false,
+            adjacency: AdjacencyMode::King,
+            stars_per_unit: 2,
+            region_star_counts,
+            report: SolveReport::default(),
+            verbose: false,
+            rules: RuleSet::all(),
+            changed: false,
+            history: MoveHistory::default(),
+            tally: None,
             #[cfg(test)]
             solution: None,
-        };
-        result.print();
-        result
+        }
     }
 
+    /// A solved board with no regions, for checking solves against. `stars`
+    /// lists each row's star columns, and every row needs as many as the first.
     #[cfg(test)]
-    pub fn solved(width: usize, height: usize, stars: Vec<(usize, usize)>) -> Self {
+    pub fn solved(width: usize, height: usize, stars: Vec<Vec<usize>>) -> Self {
+        assert_eq!(stars.len(), height, "every row needs its stars listed");
+        let stars_per_unit = stars.first().map_or(0, Vec::len);
         let mut cells = vec![
             vec![
                 Cell {
                     region: 0,
                     state: CellState::Filled
                 };
-                10
+                width
             ];
-            10
+            height
         ];
-        for row in 0..height {
-            let (star1, star2) = stars[row];
-            cells[row][star1] = Cell {
-                region: 0,
-                state: CellState::Star,
-            };
-            cells[row][star2] = Cell {
-                region: 0,
-                state: CellState::Star,
-            };
+        for (row, cols) in stars.into_iter().enumerate() {
+            assert_eq!(
+                cols.len(),
+                stars_per_unit,
+                "row {row} has the wrong number of stars"
+            );
+            for col in cols {
+                cells[row][col].state = CellState::Star;
+            }
         }
-        let result = Self {
+        Self {
             width,
             height,
             cells,
             regions: vec![],
+            wrap: false,
+            adjacency: AdjacencyMode::King,
+            stars_per_unit,
+            region_star_counts: vec![],
+            report: SolveReport::default(),
+            verbose: false,
+            rules: RuleSet::all(),
+            changed: false,
+            history: MoveHistory::default(),
+            tally: None,
             #[cfg(test)]
             solution: None,
-        };
-        result.print();
-        result
+        }
     }
 
     #[cfg(test)]
@@ -100,611 +468,4431 @@ impl Board {
             .collect()
     }
 
-    pub fn solve(&mut self) {
-        let mut past_self = self.clone();
-        loop {
-            self.enforce_rules();
+    /// Applies the deductions until none of them changes anything, and says
+    /// whether that finished the puzzle. Nothing is guessed, so a hard puzzle
+    /// can come out `Stuck`; `solve_with_cancel` searches from there.
+    pub fn solve(&mut self) -> SolveOutcome {
+        self.deduce(&AtomicBool::new(false));
+        self.outcome()
+    }
+
+    /// `solve`, using only the deductions `config` allows. They stay that way
+    /// for later solves of this board.
+    pub fn solve_with_config(&mut self, config: SolveConfig) -> SolveOutcome {
+        self.rules = config.rules;
+        self.solve()
+    }
+
+    /// `solve_with_config` with nothing set but the rules.
+    pub fn solve_with_rules(&mut self, rules: RuleSet) -> SolveOutcome {
+        self.solve_with_config(SolveConfig { rules })
+    }
+
+    /// `solve`, stopping after `max_rounds` passes of the outer loop even if
+    /// the deductions are still finding things, so a puzzle from an untrusted
+    /// source can't keep the solver busy for long. A board cut short with
+    /// blanks left comes out `Stuck`; `report` says how many passes it got.
+    pub fn solve_bounded(&mut self, max_rounds: usize) -> SolveOutcome {
+        self.deduce_within(&AtomicBool::new(false), max_rounds);
+        self.outcome()
+    }
+
+    /// The deduction loop behind `solve`, which gives up between passes once
+    /// `cancel` is set. Returns whether it ran to completion.
+    fn deduce(&mut self, cancel: &AtomicBool) -> bool {
+        self.deduce_within(cancel, usize::MAX)
+    }
+
+    /// `deduce`, stopping without complaint after `max_rounds` passes.
+    fn deduce_within(&mut self, cancel: &AtomicBool, max_rounds: usize) -> bool {
+        self.report = SolveReport::default();
+        self.forget_moves();
+        // a board that already breaks the rules has nothing to deduce, and
+        // `outcome` calls it a contradiction
+        if !self.is_legal() {
+            return true;
+        }
+        while self.report.outer_passes < max_rounds {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            self.report.outer_passes += 1;
+            let mut changed = self.enforce_rules();
+            self.changed = false;
             //blackout before adding more stars
-            self.add_required_stars_cols();
+            if self.rules.contains(RuleSet::COLS) {
+                self.run_rule(RuleKind::RequiredStarCol, Self::add_required_stars_cols);
+            }
             self.enforce_rules();
-            self.add_required_stars_rows();
+            if self.rules.contains(RuleSet::ROWS) {
+                self.run_rule(RuleKind::RequiredStarRow, Self::add_required_stars_rows);
+            }
             self.enforce_rules();
-            self.add_required_stars_region();
+            if self.rules.contains(RuleSet::REGIONS) {
+                self.run_rule(
+                    RuleKind::RequiredStarRegion,
+                    Self::add_required_stars_region,
+                );
+            }
+            if self.rules.contains(RuleSet::HIDDEN_SINGLE) {
+                self.run_rule(RuleKind::HiddenSingle, Self::add_hidden_singles);
+            }
+            if self.rules.contains(RuleSet::LAST_REGION_STAR) {
+                self.run_rule(RuleKind::LastRegionStar, Self::add_last_region_star);
+            }
 
-            if &past_self == self {
+            changed |= self.changed;
+            // nothing more is worth deducing once the board can't be finished
+            if !changed || self.is_contradictory() {
                 break;
-            } else {
-                past_self = self.clone();
-                self.print();
             }
+            self.debug_print();
         }
+        true
     }
 
-    fn enforce_rules(&mut self) {
-        let mut past_self = self.clone();
-        loop {
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.blackout_cols();
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.blackout_rows();
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.blackout_regions();
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.blackout_star_adjacencies();
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.blackout_next_to_contiguity();
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            self.eliminate_middle_of_small_empty_regions();
+    /// Where the board stands, judged only from the cells placed so far.
+    fn outcome(&self) -> SolveOutcome {
+        if self.is_contradictory() {
+            SolveOutcome::Contradiction
+        } else if self.is_solved() {
+            SolveOutcome::Solved
+        } else {
+            SolveOutcome::Stuck
+        }
+    }
 
-            self.regenerate_regions();
+    /// `(width, height)`, in cells.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
 
-            #[cfg(test)]
-            self.assert_matches_with_solution();
-            if &past_self == self {
-                break;
-            } else if true {
-                past_self = self.clone();
-                self.print();
-            }
+    /// The state of a cell, or `None` if it's off the board.
+    pub fn cell_state(&self, row: usize, col: usize) -> Option<CellState> {
+        self.cell(row, col).map(|cell| cell.state)
+    }
+
+    /// The tag of the region a cell belongs to, or `None` if it's off the board.
+    /// Shading a cell doesn't take it out of its region here.
+    pub fn region_at(&self, row: usize, col: usize) -> Option<usize> {
+        self.cell(row, col).map(|cell| cell.region)
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Option<&Cell> {
+        self.cells.get(row).and_then(|cells| cells.get(col))
+    }
+
+    /// How many stars a solution has: each row's worth, for every row.
+    pub fn total_stars(&self) -> usize {
+        self.stars_per_unit * self.height
+    }
+
+    /// How many cells are still undecided.
+    pub fn remaining_blanks(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.state == CellState::Blank)
+            .count()
+    }
+
+    /// The fraction of cells that have been decided, from 0 for a blank
+    /// board to 1 for a finished one, for progress bars. A board with no
+    /// cells counts as finished.
+    pub fn progress(&self) -> f32 {
+        let total = self.width * self.height;
+        if total == 0 {
+            return 1.0;
         }
+        (total - self.remaining_blanks()) as f32 / total as f32
     }
 
-    #[cfg(test)]
-    fn assert_matches_with_solution(&self) {
-        if let Some(solution) = &self.solution {
-            for row in 0..self.height {
-                for col in 0..self.width {
-                    if self.cells[row][col].state != CellState::Blank
-                        && self.cells[row][col].state != solution.cells[row][col].state
-                    {
-                        eprintln!(
-                            "failed to match state: self followed by solution at {row}, {col}"
-                        );
-                        self.print();
-                        solution.print();
-                        panic!();
-                    }
-                }
-            }
+    /// How many regions the board has. Tags needn't be consecutive, so this can
+    /// be less than one more than the largest tag.
+    pub fn num_regions(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.region)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Each region's tag with the cells of it that aren't shaded yet, in tag order.
+    /// Tags no cell uses are skipped. A tag names the same region for the life of the board.
+    pub fn iter_regions(&self) -> impl Iterator<Item = (usize, &[(usize, usize)])> {
+        let used = self
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.region)
+            .collect::<HashSet<_>>();
+        self.regions
+            .iter()
+            .enumerate()
+            .filter(move |(tag, _cells)| used.contains(tag))
+            .map(|(tag, cells)| (tag, cells.as_slice()))
+    }
+
+    /// The tags of regions that have all their stars and nothing else left
+    /// to decide, in tag order, for greying out finished regions.
+    pub fn solved_regions(&self) -> Vec<usize> {
+        self.iter_regions()
+            .map(|(tag, _cells)| tag)
+            .filter(|&tag| self.region_is_solved(tag))
+            .collect()
+    }
+
+    /// The tags of every other region, in tag order.
+    pub fn unsolved_regions(&self) -> Vec<usize> {
+        self.iter_regions()
+            .map(|(tag, _cells)| tag)
+            .filter(|&tag| !self.region_is_solved(tag))
+            .collect()
+    }
+
+    /// Where the stars are, as `(row, col)` in row-major order.
+    pub fn stars(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height)
+            .flat_map(move |row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cells[row][col].state == CellState::Star)
+    }
+
+    /// The stars in the region tagged `tag`, in row-major order. A tag no
+    /// cell uses has none.
+    pub fn stars_in_region(&self, tag: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.regions
+            .get(tag)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&(row, col)| self.cells[row][col].state == CellState::Star)
+    }
+
+    /// Whether the stars placed so far break no rules: none of them touch, and
+    /// no row, column or region has more than it should. Blanks aren't judged,
+    /// so this is for checking boards from elsewhere before solving them.
+    pub fn is_legal(&self) -> bool {
+        !self.stars_touch()
+            && self
+                .units()
+                .all(|unit| self.unit_counts(unit).0 <= self.quota(unit))
+    }
+
+    /// Whether the board is finished and right: every row, column and region
+    /// has exactly its stars, none of them touch, and nothing is left blank.
+    pub fn is_solved(&self) -> bool {
+        !self.stars_touch() && self.units().all(|unit| self.unit_is_solved(unit))
+    }
+
+    fn region_is_solved(&self, tag: usize) -> bool {
+        self.unit_is_solved(Unit::Region(tag))
+    }
+
+    fn unit_is_solved(&self, unit: Unit) -> bool {
+        self.unit_counts(unit) == (self.quota(unit), 0)
+    }
+
+    /// How many stars the region tagged `tag` holds, or `None` if no cell has that tag.
+    pub fn region_star_quota(&self, tag: usize) -> Option<usize> {
+        self.cells
+            .iter()
+            .flatten()
+            .any(|cell| cell.region == tag)
+            .then(|| self.region_star_counts[tag])
+    }
+
+    /// Sets how many stars every row, column and region holds, replacing any
+    /// quotas given to regions one by one. A region that's the whole board
+    /// holds every row's stars instead.
+    pub fn set_stars_per_unit(&mut self, stars_per_unit: usize) {
+        self.stars_per_unit = stars_per_unit;
+        let mut sizes = HashMap::new();
+        for cell in self.cells.iter().flatten() {
+            *sizes.entry(cell.region).or_insert(0) += 1;
+        }
+        for (tag, count) in self.region_star_counts.iter_mut().enumerate() {
+            *count = match sizes.get(&tag) {
+                None => 0,
+                // the whole board, as `Board::empty` makes, owes every row's stars
+                Some(&size) if size == self.width * self.height => stars_per_unit * self.height,
+                Some(_) => stars_per_unit,
+            };
         }
     }
 
-    pub fn print(&self) {
-        for row in &self.cells {
-            for cell in row {
-                match cell.state {
-                    CellState::Star | CellState::Filled => print!("{} ", cell.state),
-                    CellState::Blank => print!("{} ", cell.region),
-                }
-            }
-            println!();
+    /// Gives each region its own star quota, for variants where regions differ.
+    /// `counts` is indexed by region tag, and the counts for tags no cell uses are ignored.
+    /// Rows and columns keep the board-wide count, which every region also starts with.
+    pub fn set_region_star_counts(&mut self, counts: Vec<usize>) -> Result<(), BoardError> {
+        if counts.len() != self.regions.len() {
+            return Err(BoardError::WrongRegionCount {
+                expected: self.regions.len(),
+                found: counts.len(),
+            });
         }
-        println!();
+        let tags = self.cells.iter().flatten().map(|cell| cell.region);
+        let used = tags.collect::<HashSet<_>>();
+        self.region_star_counts = counts
+            .into_iter()
+            .enumerate()
+            .map(|(tag, count)| if used.contains(&tag) { count } else { 0 })
+            .collect();
+        Ok(())
     }
 
-    fn adjacencies(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        adjacencies(self.width, self.height, row, col)
+    /// Checks that the rows, the columns and the regions all add up to the same
+    /// number of stars, as they must for the board to have a solution. Boards
+    /// from `Board::try_from` always do until their quotas are changed, which
+    /// variants with fewer, fuller regions need to do.
+    pub fn validate_star_totals(&self) -> Result<(), BoardError> {
+        let rows = self.stars_per_unit * self.height;
+        let cols = self.stars_per_unit * self.width;
+        let regions = self.region_star_counts.iter().sum();
+        if rows == cols && cols == regions {
+            Ok(())
+        } else {
+            Err(BoardError::InconsistentStarTotals {
+                rows,
+                cols,
+                regions,
+            })
+        }
     }
 
-    fn blackout_star_adjacencies(&mut self) {
+    /// Records a cell the puzzle says is empty, for puzzles that come with
+    /// shading hints or progress imported from elsewhere. The solver treats it
+    /// like any cell it shaded itself. Marking an already-shaded cell does nothing.
+    pub fn mark_empty(&mut self, row: usize, col: usize) -> Result<(), BoardError> {
+        let cell = self
+            .cell(row, col)
+            .ok_or(BoardError::OutOfBounds((row, col)))?;
+        if cell.state == CellState::Star {
+            return Err(BoardError::CellIsStar((row, col)));
+        }
+        self.shade_cell(row, col);
+        self.regenerate_regions();
+        Ok(())
+    }
+
+    /// Whether the two boards look the same: the same size, with the same
+    /// regions and the same stars and shading in them, whatever the rules.
+    pub fn same_cells(&self, other: &Board) -> bool {
+        self.width == other.width && self.height == other.height && self.cells == other.cells
+    }
+
+    /// Every cell whose state differs between this board and `other`, in row-major
+    /// order, as `(row, col, before, after)` with `self` as the before.
+    /// The boards must have the same dimensions.
+    pub fn diff(
+        &self,
+        other: &Board,
+    ) -> Result<Vec<(usize, usize, CellState, CellState)>, BoardError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(BoardError::DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+
+        let mut changes = vec![];
         for row in 0..self.height {
             for col in 0..self.width {
-                if self.cells[row][col].state == CellState::Star {
-                    for (row, col) in self.adjacencies(row, col) {
-                        if self.cells[row][col].state == CellState::Star {
-                            unreachable!();
-                        }
-                        self.shade_coords(row, col);
-                    }
+                let before = self.cells[row][col].state;
+                let after = other.cells[row][col].state;
+                if before != after {
+                    changes.push((row, col, before, after));
                 }
             }
         }
+        Ok(changes)
     }
 
-    fn blackout_rows(&mut self) {
-        for row in &mut self.cells {
-            if row
-                .iter()
-                .filter(|cell| cell.state == CellState::Star)
-                .count()
-                == 2
-            {
-                for cell in row {
-                    cell.shade()
+    /// For each region tag, the tags of the regions sharing an edge with it, in
+    /// ascending order. On toroidal boards, edges across the wrap count too.
+    pub fn region_adjacency(&self) -> Vec<Vec<usize>> {
+        let tags = self.cells.iter().flatten().map(|cell| cell.region);
+        let mut adjacency = vec![vec![]; tags.max().map_or(0, |tag| tag + 1)];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let region = self.cells[row][col].region;
+                let right = self.step_forward(col, self.width).map(|col| (row, col));
+                let below = self.step_forward(row, self.height).map(|row| (row, col));
+                for (row, col) in right.into_iter().chain(below) {
+                    let neighbour = self.cells[row][col].region;
+                    if neighbour != region {
+                        adjacency[region].push(neighbour);
+                        adjacency[neighbour].push(region);
+                    }
                 }
             }
         }
-    }
-    fn blackout_cols(&mut self) {
-        for col in 0..self.width {
-            if self
-                .cells
-                .iter()
-                .map(|row| row[col])
-                .filter(|cell| cell.state == CellState::Star)
-                .count()
-                == 2
-            {
-                for row in 0..self.height {
-                    self.cells[row][col].shade()
-                }
-            }
+        for neighbours in &mut adjacency {
+            neighbours.sort();
+            neighbours.dedup();
         }
+        adjacency
     }
-    fn blackout_regions(&mut self) {
-        for region in &self.regions {
-            if region
-                .iter()
-                .map(|(row, col)| self.cells[*row][*col])
-                .filter(|cell| cell.state == CellState::Star)
-                .count()
-                == 2
-            {
-                for (row, col) in region {
-                    self.cells[*row][*col].shade()
+
+    /// For each cell, how many of its units (row, column, region) could still take
+    /// a star there: 0 means the cell is certainly empty, 3 that nothing rules it out.
+    /// A cell counts for a unit while it's blank, touches no star, and the unit
+    /// is still short of stars. Handy as a heatmap of where the solver is stuck.
+    pub fn candidate_counts(&self) -> Vec<Vec<usize>> {
+        let mut counts = vec![vec![0; self.width]; self.height];
+        for unit in self.units() {
+            let (stars, _blanks) = self.unit_counts(unit);
+            if stars >= self.quota(unit) {
+                continue;
+            }
+            for (row, col) in self.unit_cells(unit) {
+                let touches_star = self
+                    .adjacencies(row, col)
+                    .into_iter()
+                    .any(|(row, col)| self.cells[row][col].state == CellState::Star);
+                if self.cells[row][col].state == CellState::Blank && !touches_star {
+                    counts[row][col] += 1;
                 }
             }
         }
+        counts
     }
 
-    fn blackout_next_to_contiguity(&mut self) {
-        for row in 0..self.height {
-            let blanks = self.cells[row]
-                .iter()
-                .enumerate()
-                .filter(|(_col, cell)| cell.state == CellState::Blank)
-                .map(|(i, _)| i)
-                .collect::<Vec<_>>();
-            let starcount = self.cells[row]
-                .iter()
-                .filter(|cell| cell.state == CellState::Star)
-                .count();
+    /// How many passes the last `solve` took to reach its fixed point.
+    pub fn report(&self) -> SolveReport {
+        self.report
+    }
 
-            if blanks.len() == 2 && starcount == 1 && blanks[1] - blanks[0] == 1 {
-                if row != 0 {
-                    self.cells[row - 1][blanks[0]].shade();
-                    self.cells[row - 1][blanks[1]].shade();
-                }
-                if row < self.height - 1 {
-                    self.cells[row + 1][blanks[0]].shade();
-                    self.cells[row + 1][blanks[1]].shade();
-                }
-            } else if blanks.len() == 3 && starcount == 1 && blanks[2] - blanks[0] == 2 {
-                if row != 0 {
-                    self.cells[row - 1][blanks[1]].shade();
-                }
-                if row < self.height - 1 {
-                    self.cells[row + 1][blanks[1]].shade();
-                }
-            } else if blanks.len() == 4 && starcount == 0 {
-                if blanks[1] - blanks[0] == 1 {
-                    if row != 0 {
-                        self.cells[row - 1][blanks[0]].shade();
-                        self.cells[row - 1][blanks[1]].shade();
-                    }
-                    if row < self.height - 1 {
-                        self.cells[row + 1][blanks[0]].shade();
-                        self.cells[row + 1][blanks[1]].shade();
-                    }
-                }
-                if blanks[3] - blanks[2] == 1 {
-                    if row != 0 {
-                        self.cells[row - 1][blanks[2]].shade();
-                        self.cells[row - 1][blanks[3]].shade();
-                    }
-                    if row < self.height - 1 {
-                        self.cells[row + 1][blanks[2]].shade();
-                        self.cells[row + 1][blanks[3]].shade();
-                    }
+    /// Runs only the deductions that concern one region, along with shading the
+    /// neighbours of the stars they place, until the region holds its stars or
+    /// stops making progress. The rest of the board is left alone as far as possible,
+    /// which makes this handy for walking through a puzzle one region at a time.
+    pub fn solve_region(&mut self, region: usize) -> Result<SolveOutcome, BoardError> {
+        if region >= self.regions.len() {
+            return Err(BoardError::UnknownRegion(region));
+        }
+        self.forget_moves();
+
+        let mut past = self.snapshot();
+        loop {
+            self.blackout_region(region);
+            for (row, col) in self.regions[region].clone() {
+                if self.cells[row][col].state == CellState::Star {
+                    self.blackout_around_star(row, col);
                 }
             }
+            self.eliminate_middle_of_small_empty_region(region);
+            self.regenerate_regions();
+            self.add_required_stars_in_region(region, Self::add_star_locally);
+            self.add_last_star_in_region(region, Self::add_star_locally);
+            self.regenerate_regions();
+
+            let now = self.snapshot();
+            if now == past {
+                break;
+            }
+            past = now;
         }
+        Ok(self.region_outcome(region))
+    }
 
-        for col in 0..self.width {
-            let blanks = self
-                .cells
-                .iter_mut()
-                .map(|row| &mut row[col])
-                .enumerate()
-                .filter(|(_col, cell)| cell.state == CellState::Blank)
-                .map(|(i, _)| i)
-                .collect::<Vec<_>>();
-            let starcount = self
-                .cells
-                .iter_mut()
-                .map(|row| &mut row[col])
-                .filter(|cell| cell.state == CellState::Star)
-                .count();
-
-            if blanks.len() == 2 && starcount == 1 && blanks[1] - blanks[0] == 1 {
-                if col != 0 {
-                    self.cells[blanks[0]][col - 1].shade();
-                    self.cells[blanks[1]][col - 1].shade();
-                }
-                if col < self.width - 1 {
-                    self.cells[blanks[0]][col + 1].shade();
-                    self.cells[blanks[1]][col + 1].shade();
-                }
-            } else if blanks.len() == 3 && starcount == 1 && blanks[2] - blanks[0] == 2 {
-                if col != 0 {
-                    self.cells[blanks[1]][col - 1].shade();
-                }
-                if col < self.width - 1 {
-                    self.cells[blanks[1]][col + 1].shade();
-                }
-            } else if blanks.len() == 4 && starcount == 0 {
-                if blanks[1] - blanks[0] == 1 {
-                    if col != 0 {
-                        self.cells[blanks[0]][col - 1].shade();
-                        self.cells[blanks[1]][col - 1].shade();
-                    }
-                    if col < self.width - 1 {
-                        self.cells[blanks[0]][col + 1].shade();
-                        self.cells[blanks[1]][col + 1].shade();
-                    }
-                }
-                if blanks[3] - blanks[2] == 1 {
-                    if col != 0 {
-                        self.cells[blanks[2]][col - 1].shade();
-                        self.cells[blanks[3]][col - 1].shade();
-                    }
-                    if col < self.width - 1 {
-                        self.cells[blanks[2]][col + 1].shade();
-                        self.cells[blanks[3]][col + 1].shade();
-                    }
-                }
-            }
+    /// The stars and shading, packed into bits where the board is narrow
+    /// enough, for telling cheaply whether a pass changed anything.
+    fn snapshot(&self) -> Snapshot {
+        match BitBoard::new(self) {
+            Some(bits) => Snapshot::Packed(bits),
+            None => Snapshot::Cells(self.cells.clone()),
         }
     }
 
-    fn add_star_coords(&mut self, row: usize, col: usize) {
-        self.cells[row][col].star();
-        #[cfg(test)]
-        self.assert_matches_with_solution();
-        self.enforce_rules();
+    fn region_outcome(&self, region: usize) -> SolveOutcome {
+        let stars = self.regions[region]
+            .iter()
+            .copied()
+            .filter(|(row, col)| self.cells[*row][*col].state == CellState::Star)
+            .collect::<Vec<_>>();
+        let touching = stars.iter().any(|(row, col)| {
+            self.adjacencies(*row, *col)
+                .iter()
+                .any(|neighbour| stars.contains(neighbour))
+        });
+
+        let unit = Unit::Region(region);
+        if touching || self.unit_is_contradictory(unit) {
+            SolveOutcome::Contradiction
+        } else if self.unit_counts(unit) == (self.quota(unit), 0) {
+            SolveOutcome::Solved
+        } else {
+            SolveOutcome::Stuck
+        }
     }
 
-    fn shade_coords(&mut self, row: usize, col: usize) {
-        self.cells[row][col].shade();
-        #[cfg(test)]
-        self.assert_matches_with_solution();
+    /// Every row, column, and region on the board.
+    fn units(&self) -> impl Iterator<Item = Unit> {
+        (0..self.height)
+            .map(Unit::Row)
+            .chain((0..self.width).map(Unit::Col))
+            .chain((0..self.regions.len()).map(Unit::Region))
     }
 
-    fn add_required_stars_rows(&mut self) {
-        for row in self.cells.iter_mut() {
-            let mut row = row.iter_mut().collect::<Vec<_>>();
-            Self::add_required_stars_slice(&mut row)
+    /// The cells of a unit. Regions may already have dropped their shaded cells.
+    fn unit_cells(&self, unit: Unit) -> Vec<(usize, usize)> {
+        match unit {
+            Unit::Row(row) => (0..self.width).map(|col| (row, col)).collect(),
+            Unit::Col(col) => (0..self.height).map(|row| (row, col)).collect(),
+            Unit::Region(region) => self.regions[region].clone(),
         }
     }
-    fn add_required_stars_cols(&mut self) {
-        for col in 0..self.width {
-            let mut col = self
-                .cells
-                .iter_mut()
-                .map(|row| &mut row[col])
-                .collect::<Vec<&mut Cell>>();
-            Self::add_required_stars_slice(&mut col);
+
+    /// How many stars and blanks a unit holds.
+    fn unit_counts(&self, unit: Unit) -> (usize, usize) {
+        let mut stars = 0;
+        let mut blanks = 0;
+        for (row, col) in self.unit_cells(unit) {
+            match self.cells[row][col].state {
+                CellState::Star => stars += 1,
+                CellState::Blank => blanks += 1,
+                CellState::Filled => {}
+            }
         }
+        (stars, blanks)
     }
 
-    fn add_required_stars_slice(row: &mut [&mut Cell]) {
-        let blanks = row
-            .iter()
-            .enumerate()
-            .filter(|(_col, cell)| cell.state == CellState::Blank)
+    /// How many stars a unit must end up with.
+    fn quota(&self, unit: Unit) -> usize {
+        match unit {
+            Unit::Row(_) | Unit::Col(_) => self.stars_per_unit,
+            Unit::Region(region) => self.region_star_counts[region],
+        }
+    }
+
+    /// Whether a unit has too many stars, or too few blanks left to reach its quota
+    /// (or, for a line, too few that stay clear of each other).
+    fn unit_is_contradictory(&self, unit: Unit) -> bool {
+        let counts = self.unit_counts(unit);
+        let (stars, blanks) = counts;
+        let quota = self.quota(unit);
+        stars > quota || stars + blanks < quota || self.line_cannot_seat(unit, counts)
+    }
+
+    /// Whether a row or column has enough blanks for its missing stars, but not
+    /// enough of them apart from each other. A line with twice as many blanks as
+    /// it needs stars can always seat them, so only sparser ones get looked at.
+    fn line_cannot_seat(&self, unit: Unit, (stars, blanks): (usize, usize)) -> bool {
+        if matches!(unit, Unit::Region(_)) {
+            return false;
+        }
+        let need = self.quota(unit).saturating_sub(stars);
+        if need == 0 || blanks >= 2 * need {
+            return false;
+        }
+        let blanks = self
+            .unit_cells(unit)
+            .into_iter()
+            .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
             .collect::<Vec<_>>();
-        let starcount = row
-            .iter()
-            .filter(|cell| cell.state == CellState::Star)
-            .count();
-        let count = blanks.len();
+        !self.has_seating(&blanks, need)
+    }
 
-        if starcount == 0 {
-            if count <= 2 {
-                for cell in row {
-                    cell.star()
-                }
-            } else if count == 3 {
-                let cell = if blanks[1].0 - blanks[0].0 == 1 {
-                    Some(2)
-                } else if blanks[2].0 - blanks[1].0 == 1 {
-                    Some(0)
-                } else {
-                    None
-                };
+    /// Whether any two stars are neighbours.
+    fn stars_touch(&self) -> bool {
+        (0..self.height).any(|row| {
+            (0..self.width).any(|col| {
+                self.cells[row][col].state == CellState::Star
+                    && self
+                        .adjacencies(row, col)
+                        .into_iter()
+                        .any(|(row, col)| self.cells[row][col].state == CellState::Star)
+            })
+        })
+    }
 
-                if let Some(cell) = cell {
-                    row[blanks[cell].0].star();
-                }
+    /// Whether the board can no longer be completed into a solution.
+    fn is_contradictory(&self) -> bool {
+        self.stars_touch() || self.units().any(|unit| self.unit_is_contradictory(unit))
+    }
+
+    /// Runs the shading rules until a whole pass of them changes nothing, so a
+    /// rule that only fires after a later rule's stars or shading still gets its turn.
+    /// Returns whether any cell changed along the way.
+    fn enforce_rules(&mut self) -> bool {
+        // a star rule that called in here has already set the flag, and still
+        // needs to see it set on the way out
+        let entered = std::mem::take(&mut self.changed);
+        let mut changed = false;
+        loop {
+            self.changed = false;
+            self.report.inner_passes += 1;
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::COLS) {
+                self.run_rule(RuleKind::BlackoutCol, Self::blackout_cols);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::ROWS) {
+                self.run_rule(RuleKind::BlackoutRow, Self::blackout_rows);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::REGIONS) {
+                self.run_rule(RuleKind::BlackoutRegion, Self::blackout_regions);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::ADJACENCY) {
+                self.run_rule(RuleKind::StarAdjacency, Self::blackout_star_adjacencies);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::CONTIGUITY) {
+                self.run_rule(RuleKind::Contiguity, Self::blackout_next_to_contiguity);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::SMALL_REGION) {
+                self.run_rule(
+                    RuleKind::SmallRegion,
+                    Self::eliminate_middle_of_small_empty_regions,
+                );
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::CONFINEMENT) {
+                self.run_rule(
+                    RuleKind::Confinement,
+                    Self::blackout_beside_confined_regions,
+                );
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::BLOCKS) {
+                self.run_rule(RuleKind::Block, Self::blackout_full_2x2_blocks);
+            }
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if self.rules.contains(RuleSet::PIGEONHOLE) {
+                self.run_rule(
+                    RuleKind::RowPigeonhole,
+                    Self::blackout_region_row_pigeonhole,
+                );
+                self.run_rule(
+                    RuleKind::ColPigeonhole,
+                    Self::blackout_region_col_pigeonhole,
+                );
             }
-        } else if starcount == 1 && count == 1 {
-            for cell in row {
-                cell.star()
+
+            self.regenerate_regions();
+
+            #[cfg(test)]
+            self.assert_matches_with_solution();
+            if !self.changed {
+                self.changed = entered || changed;
+                return changed;
             }
+            changed = true;
+            self.debug_print();
         }
     }
 
-    fn add_required_stars_region(&mut self) {
-        for region in self.regions.clone() {
-            let blanks = region
-                .iter()
-                .filter(|(row, col)| self.cells[*row][*col].state == CellState::Blank)
-                .collect::<Vec<_>>();
-            let starcount = region
-                .iter()
-                .filter(|(row, col)| self.cells[*row][*col].state == CellState::Star)
-                .count();
-            let count = blanks.len();
-
-            if starcount == 0 {
-                if count <= 2 {
-                    for (row, col) in region {
-                        self.add_star_coords(row, col);
-                    }
-                } else if count == 3 {
-                    if adjacencies(self.width, self.height, blanks[0].0, blanks[0].1)
-                        .contains(blanks[1])
-                    {
-                        self.add_star_coords(blanks[2].0, blanks[2].1);
-                    } else if adjacencies(self.width, self.height, blanks[1].0, blanks[1].1)
-                        .contains(blanks[2])
-                    {
-                        self.add_star_coords(blanks[0].0, blanks[0].1);
-                    } else if adjacencies(self.width, self.height, blanks[0].0, blanks[0].1)
-                        .contains(blanks[2])
-                    {
-                        self.add_star_coords(blanks[1].0, blanks[1].1);
-                    }
-                }
-            } else if starcount == 1 && count == 1 {
-                for (row, col) in region {
-                    self.add_star_coords(row, col);
-                }
+    /// Runs one of the deductions. While `solve_with_stats` is counting, the
+    /// deduction is credited with the cells it decided itself, and the shading
+    /// rules a star rule sets off are credited with theirs.
+    fn run_rule(&mut self, kind: RuleKind, rule: fn(&mut Self)) {
+        let outer = self
+            .tally
+            .as_mut()
+            .map(|tally| std::mem::take(&mut tally.decided));
+        rule(self);
+        if let (Some(tally), Some(outer)) = (&mut self.tally, outer) {
+            if tally.decided > 0 {
+                *tally.stats.per_rule.entry(kind).or_insert(0) += 1;
             }
+            tally.decided = outer;
         }
+        self.debug_check_invariants(kind.name());
     }
 
-    fn eliminate_middle_of_small_empty_regions(&mut self) {
-        self.print();
-        for region in self.regions.clone() {
-            let starcount = self.regional_stars(&region);
-            if region.is_empty() || starcount != 0 {
-                continue;
+    /// In debug builds, panics if `rule` just left some unit over its star quota
+    /// or two stars touching. Rules only ever run on boards that were legal
+    /// when solving began, and `place_star` keeps them that way, so either
+    /// would be a bug in the rule rather than in the puzzle.
+    #[cfg(debug_assertions)]
+    fn debug_check_invariants(&self, rule: &str) {
+        if let Some(unit) = self
+            .units()
+            .find(|unit| self.unit_counts(*unit).0 > self.quota(*unit))
+        {
+            panic!("{rule} overfilled {unit}");
+        }
+        assert!(!self.stars_touch(), "{rule} left two stars touching");
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn debug_check_invariants(&self, _rule: &str) {}
+
+    #[cfg(test)]
+    fn assert_matches_with_solution(&self) {
+        if let Some(solution) = &self.solution {
+            if let Err(wrong) = self.verify_against(solution) {
+                let (row, col) = wrong[0];
+                eprintln!("failed to match state: self followed by solution at {row}, {col}");
+                self.print();
+                solution.print();
+                panic!();
             }
+        }
+    }
 
-            let mut min_row = usize::MAX;
-            let mut max_row = usize::MIN;
-            let mut min_col = usize::MAX;
-            let mut max_col = usize::MIN;
-
-            for (row, col) in region {
-                min_row = min_row.min(row);
-                min_col = min_col.min(col);
-                max_row = max_row.max(row);
-                max_col = max_col.max(col);
-            }
-            let width = max_col - min_col + 1;
-            let height = max_row - min_row + 1;
-            let area = width * height;
-            if area <= 6 && width <= 3 && height <= 3 {
-                //small region detected :)
-                //time to find the middle
-                if width <= height {
-                    let mid_row = max_row - 1;
-                    for col in min_col..=max_col {
-                        self.shade_coords(mid_row, col);
-                    }
-                    if min_row != 0 {
-                        for col in min_col..=max_col {
-                            self.shade_coords(min_row - 1, col);
-                        }
-                    }
-                    if max_row < self.height - 1 {
-                        for col in min_col..=max_col {
-                            self.shade_coords(max_row + 1, col);
-                        }
-                    }
-                } else {
-                    let mid_col = max_col - 1;
-                    for row in min_row..=max_row {
-                        self.shade_coords(row, mid_col);
-                    }
-                    if min_col != 0 {
-                        for row in min_row..=max_row {
-                            self.shade_coords(row, min_col - 1);
-                        }
-                    }
-                    if max_col < self.width - 1 {
-                        for row in min_row..=max_row {
-                            self.shade_coords(row, max_col + 1);
-                        }
-                    }
-                }
+    /// Prints the board to stdout, as `write_to` lays it out.
+    ///
+    /// # Panics
+    ///
+    /// If writing to stdout fails, like `println!`.
+    pub fn print(&self) {
+        self.write_to(&mut std::io::stdout().lock())
+            .expect("failed printing to stdout");
+    }
+
+    /// Writes the board as `Display` shows it, followed by a blank line to set
+    /// it apart from whatever comes next.
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        writeln!(w, "{self}")
+    }
+
+    /// `write_to`, drawing the cells with `glyphs` instead.
+    pub fn write_with_glyphs<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        glyphs: &GlyphSet,
+    ) -> std::io::Result<()> {
+        writeln!(w, "{}", self.to_string_with_glyphs(glyphs))
+    }
+
+    /// The board as `Display` lays it out, drawing the cells with `glyphs`.
+    pub fn to_string_with_glyphs(&self, glyphs: &GlyphSet) -> String {
+        struct WithGlyphs<'a>(&'a Board, &'a GlyphSet);
+
+        impl Display for WithGlyphs<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_with(f, self.1)
             }
         }
+
+        WithGlyphs(self, glyphs).to_string()
     }
 
-    fn regional_stars(&self, region: &[(usize, usize)]) -> usize {
-        region
+    fn fmt_with(&self, f: &mut std::fmt::Formatter<'_>, glyphs: &GlyphSet) -> std::fmt::Result {
+        let width = match glyphs.blank {
+            Some(_) => 1,
+            None => self
+                .cells
+                .iter()
+                .flatten()
+                .map(|cell| cell.region.to_string().len())
+                .max()
+                .unwrap_or(1),
+        };
+        for row in &self.cells {
+            let row = row
+                .iter()
+                .map(|cell| match (cell.state, glyphs.blank) {
+                    (CellState::Blank, None) => format!("{:>width$}", cell.region),
+                    (state, _) => format!("{:>width$}", glyphs.glyph(state)),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+
+    fn debug_print(&self) {
+        if self.verbose {
+            self.print();
+        }
+    }
+
+    /// Labels every star currently on the board as `R{row}C{col}`, in row-major order.
+    /// `base` is the number given to the first row and column: 1 for the
+    /// spreadsheet-style labels used on puzzle forums, 0 to match board indices.
+    /// This only describes the answer once the board has been solved.
+    pub fn solution_labels(&self, base: usize) -> Vec<String> {
+        self.cells
             .iter()
-            .filter(|(row, col)| self.cells[*row][*col].state == CellState::Star)
-            .count()
+            .enumerate()
+            .flat_map(|(row_index, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_col_index, cell)| cell.state == CellState::Star)
+                    .map(move |(col_index, _cell)| {
+                        format!("R{}C{}", row_index + base, col_index + base)
+                    })
+            })
+            .collect()
     }
 
-    fn regenerate_regions(&mut self) {
-        self.regions = self
+    /// The board turned a quarter turn clockwise, stars, shading and region tags included.
+    /// Its width and height swap.
+    pub fn rotated_90(&self) -> Board {
+        let height = self.height;
+        self.transformed(self.height, self.width, |row, col| (col, height - 1 - row))
+    }
+
+    /// The board flipped left to right, stars, shading and region tags included.
+    pub fn mirrored(&self) -> Board {
+        let width = self.width;
+        self.transformed(self.width, self.height, |row, col| (row, width - 1 - col))
+    }
+
+    /// A `width` by `height` copy with the cell at `(row, col)` moved to `to(row, col)`.
+    fn transformed(
+        &self,
+        width: usize,
+        height: usize,
+        to: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Board {
+        let mut cells = vec![vec![None; width]; height];
+        for (row, old) in self.cells.iter().enumerate() {
+            for (col, cell) in old.iter().enumerate() {
+                let (row, col) = to(row, col);
+                cells[row][col] = Some(*cell);
+            }
+        }
+        let cells = cells
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|cell| cell.expect("every cell moves somewhere"))
+                    .collect()
+            })
+            .collect();
+        let regions = self
             .regions
             .iter()
             .map(|region| {
-                region
+                let mut region = region
                     .iter()
-                    .filter(|(row, col)| self.cells[*row][*col].state != CellState::Filled)
-                    .copied()
-                    .collect::<Vec<(usize, usize)>>()
+                    .map(|&(row, col)| to(row, col))
+                    .collect::<Vec<_>>();
+                region.sort();
+                region
             })
-            .collect()
+            .collect();
+        let board = Board {
+            width,
+            height,
+            cells,
+            regions,
+            history: MoveHistory::default(),
+            ..self.clone()
+        };
+        #[cfg(test)]
+        let board = Board {
+            solution: None,
+            ..board
+        };
+        board
     }
-}
 
-fn adjacencies(width: usize, height: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
-    if row >= height || col >= width {
-        return vec![];
+    /// Sets whether solving prints the board after every pass, for watching
+    /// the deductions at work. Boards start out silent.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
     }
 
-    let mut adjacencies = vec![];
+    /// Toroidal boards wrap around their edges, so cells on opposite edges touch.
+    /// Boards don't wrap unless this is set.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
 
-    if row > 0 {
-        adjacencies.push((row - 1, col));
-        if col > 0 {
-            adjacencies.push((row - 1, col - 1));
-        }
-        if col < width - 1 {
-            adjacencies.push((row - 1, col + 1));
-        }
+    /// Which cells count as touching. Stars touch diagonally too unless this is set.
+    pub fn set_adjacency_mode(&mut self, adjacency: AdjacencyMode) {
+        self.adjacency = adjacency;
     }
-    if row < height - 1 {
-        adjacencies.push((row + 1, col));
-        if col > 0 {
-            adjacencies.push((row + 1, col - 1));
-        }
-        if col < width - 1 {
-            adjacencies.push((row + 1, col + 1));
+
+    fn adjacencies(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        match (self.wrap, self.adjacency) {
+            (false, AdjacencyMode::King) => adjacencies(self.width, self.height, row, col),
+            (false, AdjacencyMode::Orthogonal) => {
+                orthogonal_adjacencies(self.width, self.height, row, col)
+            }
+            (true, AdjacencyMode::King) => wrapping_adjacencies(self.width, self.height, row, col),
+            (true, AdjacencyMode::Orthogonal) => {
+                let mut around = wrapping_adjacencies(self.width, self.height, row, col);
+                around.retain(|&(r, c)| r == row || c == col);
+                around
+            }
         }
     }
-    if col > 0 {
-        adjacencies.push((row, col - 1));
-    }
-    if col < width - 1 {
-        adjacencies.push((row, col + 1));
+
+    /// The index before `index` along an axis of length `len`, wrapping if the board does.
+    fn step_back(&self, index: usize, len: usize) -> Option<usize> {
+        if index > 0 {
+            Some(index - 1)
+        } else if self.wrap && len > 1 {
+            Some(len - 1)
+        } else {
+            None
+        }
     }
 
-    adjacencies
-}
+    /// The index after `index` along an axis of length `len`, wrapping if the board does.
+    fn step_forward(&self, index: usize, len: usize) -> Option<usize> {
+        if index + 1 < len {
+            Some(index + 1)
+        } else if self.wrap && len > 1 {
+            Some(0)
+        } else {
+            None
+        }
+    }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Cell {
-    /// indexes into the `regions` member of the board struct
-    region: usize,
-    state: CellState,
-}
+    /// The neighbouring rows (or columns) of `index` along an axis of length `len`.
+    fn beside(&self, index: usize, len: usize) -> Vec<usize> {
+        let mut beside = self
+            .step_back(index, len)
+            .into_iter()
+            .chain(self.step_forward(index, len))
+            .collect::<Vec<_>>();
+        beside.dedup();
+        beside
+    }
 
-impl Cell {
-    fn shade(&mut self) {
-        if self.state == CellState::Blank {
-            self.state = CellState::Filled;
+    fn blackout_star_adjacencies(&mut self) {
+        for (row, col) in self.stars().collect::<Vec<_>>() {
+            self.blackout_around_star(row, col);
         }
     }
-    fn star(&mut self) {
-        if self.state == CellState::Blank {
-            self.state = CellState::Star;
+
+    /// Touching stars are left for `is_contradictory` to find.
+    fn blackout_around_star(&mut self, row: usize, col: usize) {
+        for (row, col) in self.adjacencies(row, col) {
+            self.shade_coords(row, col);
         }
     }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum CellState {
-    Blank,
-    Star,
-    Filled,
-}
 
-impl Display for CellState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Blank => '-',
-                Self::Star => 'X',
-                Self::Filled => '#',
+    fn blackout_rows(&mut self) {
+        for row in 0..self.height {
+            if self.unit_counts(Unit::Row(row)).0 == self.stars_per_unit {
+                for col in 0..self.width {
+                    self.changed |= self.shade_cell(row, col);
+                }
             }
-        )
+        }
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    fn test_board_sample() -> Board {
+    fn blackout_cols(&mut self) {
+        for col in 0..self.width {
+            if self.unit_counts(Unit::Col(col)).0 == self.stars_per_unit {
+                for row in 0..self.height {
+                    self.changed |= self.shade_cell(row, col);
+                }
+            }
+        }
+    }
+    fn blackout_regions(&mut self) {
+        for region in 0..self.regions.len() {
+            self.blackout_region(region);
+        }
+    }
+    fn blackout_region(&mut self, region: usize) {
+        let quota = self.region_star_counts[region];
+        if self.regional_stars(&self.regions[region]) == quota {
+            for (row, col) in self.regions[region].clone() {
+                self.changed |= self.shade_cell(row, col);
+            }
+        }
+    }
+
+    /// A line's missing stars have to go somewhere among its blanks. Whichever
+    /// way they're seated, a cell in a neighbouring line that touches one of them
+    /// every time can't be a star.
+    fn blackout_next_to_contiguity(&mut self) {
+        for axis in [Axis::Rows, Axis::Cols] {
+            let lines = self.line_count(axis);
+            for index in 0..lines {
+                let line = axis.line(index);
+                let (stars, _blanks) = self.unit_counts(line);
+                let need = self.quota(line).saturating_sub(stars);
+                if need == 0 {
+                    continue;
+                }
+                let blanks = self
+                    .unit_cells(line)
+                    .into_iter()
+                    .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                    .collect::<Vec<_>>();
+                let seatings = self.star_seatings(&blanks, need);
+                if seatings.is_empty() {
+                    continue;
+                }
+
+                let neighbours = self
+                    .beside(index, lines)
+                    .into_iter()
+                    .flat_map(|index| self.unit_cells(axis.line(index)))
+                    .collect::<Vec<_>>();
+                for (row, col) in neighbours {
+                    let around = self.adjacencies(row, col);
+                    if seatings
+                        .iter()
+                        .all(|seating| seating.iter().any(|star| around.contains(star)))
+                    {
+                        self.shade_coords(row, col);
+                    }
+                }
+            }
+        }
+    }
+
+    /// How many rows, or columns, the board has.
+    fn line_count(&self, axis: Axis) -> usize {
+        match axis {
+            Axis::Rows => self.height,
+            Axis::Cols => self.width,
+        }
+    }
+
+    /// Stars a blank cell. Returns whether it was blank.
+    fn star_cell(&mut self, row: usize, col: usize) -> bool {
+        let starred = self.cells[row][col].star();
+        if starred {
+            self.count_decided(CellState::Star);
+        }
+        starred
+    }
+
+    /// Stars a blank cell for a rule, unless a star there would touch another
+    /// or overfill its row, column or region. Such a cell is left for the
+    /// shading rules, which leave its unit short for `is_contradictory` to
+    /// report. Returns whether it was starred.
+    fn place_star(&mut self, row: usize, col: usize) -> bool {
+        self.cells[row][col].state == CellState::Blank
+            && self.star_fits(row, col)
+            && self.star_cell(row, col)
+    }
+
+    /// Whether a star at `(row, col)` would touch no other star and find room
+    /// in its row, column and region.
+    fn star_fits(&self, row: usize, col: usize) -> bool {
+        let tag = self.cells[row][col].region;
+        let region = (tag < self.regions.len()).then_some(Unit::Region(tag));
+        !self
+            .adjacencies(row, col)
+            .into_iter()
+            .any(|(row, col)| self.cells[row][col].state == CellState::Star)
+            && [Unit::Row(row), Unit::Col(col)]
+                .into_iter()
+                .chain(region)
+                .all(|unit| self.unit_counts(unit).0 < self.quota(unit))
+    }
+
+    /// Shades a blank cell. Returns whether it was blank.
+    fn shade_cell(&mut self, row: usize, col: usize) -> bool {
+        let shaded = self.cells[row][col].shade();
+        if shaded {
+            self.count_decided(CellState::Filled);
+        }
+        shaded
+    }
+
+    /// Sets a cell to any state at all, for undoing moves and loading boards.
+    /// Unlike `star_cell` and `shade_cell`, this can take a decided cell back
+    /// to blank.
+    fn set_state(&mut self, row: usize, col: usize, state: CellState) {
+        self.cells[row][col].state = state;
+    }
+
+    fn add_star_coords(&mut self, row: usize, col: usize) {
+        self.changed |= self.place_star(row, col);
+        #[cfg(test)]
+        self.assert_matches_with_solution();
+        self.enforce_rules();
+    }
+
+    /// Stars a cell and shades only its neighbours, leaving the rest of the board alone.
+    fn add_star_locally(&mut self, row: usize, col: usize) {
+        self.changed |= self.place_star(row, col);
+        #[cfg(test)]
+        self.assert_matches_with_solution();
+        if self.cells[row][col].state == CellState::Star {
+            self.blackout_around_star(row, col);
+        }
+    }
+
+    fn shade_coords(&mut self, row: usize, col: usize) {
+        self.changed |= self.shade_cell(row, col);
+        #[cfg(test)]
+        self.assert_matches_with_solution();
+    }
+
+    fn add_required_stars_rows(&mut self) {
+        for row in 0..self.height {
+            self.add_required_stars_line(Unit::Row(row));
+        }
+    }
+    fn add_required_stars_cols(&mut self) {
+        for col in 0..self.width {
+            self.add_required_stars_line(Unit::Col(col));
+        }
+    }
+
+    /// Works out a row or column's required stars on a copy of its cells, then
+    /// places them on the board one at a time.
+    fn add_required_stars_line(&mut self, unit: Unit) {
+        let line = self.unit_cells(unit);
+        let mut cells = line
+            .iter()
+            .map(|&(row, col)| self.cells[row][col])
+            .collect::<Vec<_>>();
+        let mut slice = cells.iter_mut().collect::<Vec<_>>();
+        if !Self::add_required_stars_slice(&mut slice, self.stars_per_unit, self.wrap) {
+            return;
+        }
+        for (cell, (row, col)) in cells.into_iter().zip(line) {
+            if cell.state == CellState::Star {
+                self.changed |= self.place_star(row, col);
+            }
+        }
+    }
+
+    /// `quota` is how many stars the line holds, and `wrap` means its two ends
+    /// touch. When the only cells left for the line's stars touch, nothing is
+    /// starred; `is_contradictory` reports the line instead. With one blank to
+    /// spare, a blank is starred if leaving it out is the only way to seat the rest.
+    /// Returns whether anything was starred.
+    fn add_required_stars_slice(row: &mut [&mut Cell], quota: usize, wrap: bool) -> bool {
+        let len = row.len();
+        let blanks = row
+            .iter()
+            .enumerate()
+            .filter(|(_col, cell)| cell.state == CellState::Blank)
+            .map(|(col, _cell)| col)
+            .collect::<Vec<_>>();
+        let starcount = row
+            .iter()
+            .filter(|cell| cell.state == CellState::Star)
+            .count();
+        let need = quota.saturating_sub(starcount);
+        let count = blanks.len();
+
+        let mut starred = false;
+        if need == 0 {
+            return starred;
+        }
+        if count <= need {
+            let seated = row
+                .iter()
+                .enumerate()
+                .filter(|(_col, cell)| cell.state != CellState::Filled)
+                .map(|(col, _cell)| col)
+                .collect::<Vec<_>>();
+            if !Self::any_touch(&seated, len, wrap) {
+                for cell in row {
+                    starred |= cell.star();
+                }
+            }
+        } else if count == need + 1 {
+            let spares = (0..count)
+                .filter(|&spare| {
+                    let mut rest = blanks.clone();
+                    rest.remove(spare);
+                    !Self::any_touch(&rest, len, wrap)
+                })
+                .collect::<Vec<_>>();
+            if spares.is_empty() {
+                return starred;
+            }
+            for (index, col) in blanks.into_iter().enumerate() {
+                if !spares.contains(&index) {
+                    starred |= row[col].star();
+                }
+            }
+        }
+        starred
+    }
+
+    /// Whether any two of `cols`, in increasing order along a line of length `len`, touch.
+    fn any_touch(cols: &[usize], len: usize, wrap: bool) -> bool {
+        cols.windows(2).any(|pair| pair[1] - pair[0] == 1)
+            || (wrap && len > 2 && cols.first() == Some(&0) && cols.last() == Some(&(len - 1)))
+    }
+
+    fn add_required_stars_region(&mut self) {
+        for region in 0..self.regions.len() {
+            self.add_required_stars_in_region(region, Self::add_star_coords);
+        }
+    }
+
+    /// `place_star` decides how far each new star propagates before the next is placed.
+    fn add_required_stars_in_region(
+        &mut self,
+        region: usize,
+        place_star: fn(&mut Self, usize, usize),
+    ) {
+        let quota = self.region_star_counts[region];
+        let region = self.regions[region].clone();
+        let blanks = region
+            .iter()
+            .copied()
+            .filter(|(row, col)| self.cells[*row][*col].state == CellState::Blank)
+            .collect::<Vec<_>>();
+        let starcount = self.regional_stars(&region);
+        let count = blanks.len();
+        let need = quota.saturating_sub(starcount);
+
+        if need == 0 {
+            return;
+        }
+        if count <= need {
+            for (row, col) in blanks {
+                place_star(self, row, col);
+            }
+        } else if count == need + 1 {
+            let seatings = self.star_seatings(&blanks, need);
+            if !seatings.is_empty() {
+                for (row, col) in blanks {
+                    if seatings.iter().all(|seating| seating.contains(&(row, col))) {
+                        place_star(self, row, col);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every way to seat `k` stars among the cells of a line without any two touching,
+    /// each as a list of the starred cells in the order given. Only adjacency within
+    /// `cells` is considered; whether a seating clashes with stars in neighbouring
+    /// lines is up to the caller.
+    pub fn line_placements(&self, cells: &[(usize, usize)], k: usize) -> Vec<Vec<(usize, usize)>> {
+        self.star_seatings(cells, k)
+    }
+
+    /// Every way to put `stars` stars among `cells` without any two touching.
+    fn star_seatings(&self, cells: &[(usize, usize)], stars: usize) -> Vec<Vec<(usize, usize)>> {
+        if stars == 0 {
+            return vec![vec![]];
+        }
+        let mut seatings = vec![];
+        for (index, &(row, col)) in cells.iter().enumerate() {
+            let around = self.adjacencies(row, col);
+            let rest = cells[index + 1..]
+                .iter()
+                .copied()
+                .filter(|cell| !around.contains(cell))
+                .collect::<Vec<_>>();
+            for mut seating in self.star_seatings(&rest, stars - 1) {
+                seating.insert(0, (row, col));
+                seatings.push(seating);
+            }
+        }
+        seatings
+    }
+
+    /// Whether `stars` stars fit among `cells` without any two touching. Stops at
+    /// the first seating found, so a big region costs no more than a small one.
+    fn has_seating(&self, cells: &[(usize, usize)], stars: usize) -> bool {
+        if stars == 0 {
+            return true;
+        }
+        cells.len() >= stars
+            && cells.iter().enumerate().any(|(index, &(row, col))| {
+                let around = self.adjacencies(row, col);
+                let rest = cells[index + 1..]
+                    .iter()
+                    .copied()
+                    .filter(|cell| !around.contains(cell))
+                    .collect::<Vec<_>>();
+                self.has_seating(&rest, stars - 1)
+            })
+    }
+
+    /// Stars the blanks of a row, column or region that turn up in every way of
+    /// seating the stars it's still missing. With one of them forced the rest
+    /// have fewer places left, so this can find a star that none of the
+    /// "few blanks left" rules see.
+    fn add_hidden_singles(&mut self) {
+        for unit in self.units().collect::<Vec<_>>() {
+            let (stars, _blanks) = self.unit_counts(unit);
+            let need = self.quota(unit).saturating_sub(stars);
+            let blanks = self
+                .unit_cells(unit)
+                .into_iter()
+                .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                .collect::<Vec<_>>();
+            // a unit this roomy has too many seatings for any cell to be in them all
+            if need == 0 || blanks.len() > 16 * need {
+                continue;
+            }
+            let seatings = self.star_seatings(&blanks, need);
+            if seatings.is_empty() {
+                continue;
+            }
+            for (row, col) in blanks {
+                if seatings.iter().all(|seating| seating.contains(&(row, col))) {
+                    self.add_star_coords(row, col);
+                }
+            }
+        }
+    }
+
+    fn add_last_region_star(&mut self) {
+        for region in 0..self.regions.len() {
+            self.add_last_star_in_region(region, Self::add_star_coords);
+        }
+    }
+
+    /// Once a region is down to its last star, its blanks touching a star are out,
+    /// and the last star has to go in whatever is left. A single survivor is starred;
+    /// two touching survivors shade every cell that neighbours both of them.
+    /// A region with no survivors is left short of stars with no blanks, which can't be satisfied.
+    fn add_last_star_in_region(&mut self, region: usize, place_star: fn(&mut Self, usize, usize)) {
+        let quota = self.region_star_counts[region];
+        let region = self.regions[region].clone();
+        if self.regional_stars(&region) + 1 != quota {
+            return;
+        }
+        let (candidates, touching): (Vec<_>, Vec<_>) = region
+            .iter()
+            .copied()
+            .filter(|(row, col)| self.cells[*row][*col].state == CellState::Blank)
+            .partition(|(row, col)| {
+                self.adjacencies(*row, *col)
+                    .into_iter()
+                    .all(|(row, col)| self.cells[row][col].state != CellState::Star)
+            });
+        for (row, col) in touching {
+            self.shade_coords(row, col);
+        }
+
+        if let [(row, col)] = candidates[..] {
+            place_star(self, row, col);
+        } else if let [first, second] = candidates[..] {
+            let around_second = self.adjacencies(second.0, second.1);
+            if around_second.contains(&first) {
+                for (row, col) in self.adjacencies(first.0, first.1) {
+                    if (row, col) != second && around_second.contains(&(row, col)) {
+                        self.shade_coords(row, col);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A region whose blanks all lie in one line puts all its missing stars in that line.
+    /// If that's as many as the line is missing, the rest of the line is out.
+    fn blackout_beside_confined_regions(&mut self) {
+        for region in 0..self.regions.len() {
+            let (stars, blanks) = self.unit_counts(Unit::Region(region));
+            let need = self.quota(Unit::Region(region)).saturating_sub(stars);
+            if need == 0 || blanks == 0 {
+                continue;
+            }
+            let blanks = self.regions[region]
+                .iter()
+                .copied()
+                .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                .collect::<Vec<_>>();
+            let (row, col) = blanks[0];
+            let lines = [
+                blanks
+                    .iter()
+                    .all(|cell| cell.0 == row)
+                    .then_some(Unit::Row(row)),
+                blanks
+                    .iter()
+                    .all(|cell| cell.1 == col)
+                    .then_some(Unit::Col(col)),
+            ];
+            for line in lines.into_iter().flatten() {
+                let (line_stars, _line_blanks) = self.unit_counts(line);
+                if self.quota(line).saturating_sub(line_stars) != need {
+                    continue;
+                }
+                for (row, col) in self.unit_cells(line) {
+                    if self.cells[row][col].region != region {
+                        self.shade_coords(row, col);
+                    }
+                }
+            }
+        }
+    }
+
+    /// If some regions have all their blanks within a band of rows, and between
+    /// them owe exactly as many stars as those rows do, every star the rows are
+    /// missing goes to those regions. The rest of the band is shaded. A band of
+    /// one row with one region in it is what `blackout_beside_confined_regions`
+    /// already covers; this takes any band short of the whole board.
+    fn blackout_region_row_pigeonhole(&mut self) {
+        self.blackout_region_pigeonhole(Axis::Rows);
+    }
+
+    /// `blackout_region_row_pigeonhole`, for bands of columns.
+    fn blackout_region_col_pigeonhole(&mut self) {
+        self.blackout_region_pigeonhole(Axis::Cols);
+    }
+
+    fn blackout_region_pigeonhole(&mut self, axis: Axis) {
+        let lines = self.line_count(axis);
+        for first in 0..lines {
+            for last in first..lines {
+                if first == 0 && last == lines - 1 {
+                    continue;
+                }
+                let band = first..=last;
+                let inside = (0..self.regions.len())
+                    .filter(|&region| {
+                        let blanks = self.regions[region]
+                            .iter()
+                            .copied()
+                            .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                            .collect::<Vec<_>>();
+                        span(&blanks, axis)
+                            .is_some_and(|(low, high)| band.contains(&low) && band.contains(&high))
+                    })
+                    .collect::<Vec<_>>();
+                if inside.is_empty() {
+                    continue;
+                }
+                let owed = |unit: Unit| self.quota(unit).saturating_sub(self.unit_counts(unit).0);
+                let region_need = inside
+                    .iter()
+                    .map(|&region| owed(Unit::Region(region)))
+                    .sum::<usize>();
+                let line_need = band
+                    .clone()
+                    .map(|line| owed(axis.line(line)))
+                    .sum::<usize>();
+                if region_need != line_need {
+                    continue;
+                }
+                for line in band {
+                    for (row, col) in self.unit_cells(axis.line(line)) {
+                        if !inside.contains(&self.cells[row][col].region) {
+                            self.shade_coords(row, col);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// No 2x2 block can hold two stars without them touching, and a star
+    /// already shades the rest of its block through adjacency. What's left is
+    /// a region down to its last star with its blanks inside one block: the
+    /// star lands somewhere in there, so any cell touching all of those blanks,
+    /// like the corner that completes an L of three, is shaded.
+    fn blackout_full_2x2_blocks(&mut self) {
+        for region in 0..self.regions.len() {
+            let (stars, _blanks) = self.unit_counts(Unit::Region(region));
+            if self.quota(Unit::Region(region)).saturating_sub(stars) != 1 {
+                continue;
+            }
+            let blanks = self.regions[region]
+                .iter()
+                .copied()
+                .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                .collect::<Vec<_>>();
+            let Some(&(row, col)) = blanks.first() else {
+                continue;
+            };
+            let extent = |axis| span(&blanks, axis).map_or(0, |(low, high)| high - low);
+            if extent(Axis::Rows) > 1 || extent(Axis::Cols) > 1 {
+                continue;
+            }
+            let mut touching_all = self.adjacencies(row, col);
+            touching_all.retain(|cell| {
+                !blanks.contains(cell)
+                    && blanks[1..]
+                        .iter()
+                        .all(|&(row, col)| self.adjacencies(row, col).contains(cell))
+            });
+            for (row, col) in touching_all {
+                self.shade_coords(row, col);
+            }
+        }
+    }
+
+    fn eliminate_middle_of_small_empty_regions(&mut self) {
+        self.debug_print();
+        for region in 0..self.regions.len() {
+            self.eliminate_middle_of_small_empty_region(region);
+        }
+    }
+
+    /// Works through every way a small, empty region can seat its two stars:
+    /// its cells that are in none of them and the cells around it that all of
+    /// them touch are shaded. Going by the seatings rather than the region's
+    /// outline means a square region gets its eliminations whichever way it runs.
+    fn eliminate_middle_of_small_empty_region(&mut self, region: usize) {
+        let quota = self.region_star_counts[region];
+        let region = self.regions[region].clone();
+        let starcount = self.regional_stars(&region);
+        // a lone cell has no middle, and can't hold two stars anyway
+        if region.len() < 2 || starcount != 0 || quota != 2 {
+            return;
+        }
+
+        let extent = |axis| span(&region, axis).map_or(0, |(low, high)| high - low + 1);
+        if extent(Axis::Cols) > 3 || extent(Axis::Rows) > 3 {
+            return;
+        }
+        //small region detected :)
+        let seatings = self.star_seatings(&region, quota);
+        if seatings.is_empty() {
+            return;
+        }
+
+        for &(row, col) in &region {
+            if !seatings.iter().any(|seating| seating.contains(&(row, col))) {
+                self.shade_coords(row, col);
+            }
+        }
+        let mut outside = region
+            .iter()
+            .flat_map(|&(row, col)| self.adjacencies(row, col))
+            .filter(|cell| !region.contains(cell))
+            .collect::<Vec<_>>();
+        outside.sort_unstable();
+        outside.dedup();
+        for (row, col) in outside {
+            if self.cells[row][col].state != CellState::Blank {
+                continue;
+            }
+            let around = self.adjacencies(row, col);
+            if seatings
+                .iter()
+                .all(|seating| seating.iter().any(|star| around.contains(star)))
+            {
+                self.shade_coords(row, col);
+            }
+        }
+    }
+
+    fn regional_stars(&self, region: &[(usize, usize)]) -> usize {
+        region
+            .iter()
+            .filter(|(row, col)| self.cells[*row][*col].state == CellState::Star)
+            .count()
+    }
+
+    /// Lists every region's unshaded cells afresh, for when cells may have
+    /// been unshaded, which `regenerate_regions` can't put back.
+    fn reindex_regions(&mut self) {
+        let mut regions = vec![vec![]; self.regions.len()];
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if cell.state != CellState::Filled {
+                    regions[cell.region].push((row, col));
+                }
+            }
+        }
+        self.regions = regions;
+    }
+
+    /// Drops shaded cells from every region, keeping each region at its own index.
+    fn regenerate_regions(&mut self) {
+        self.regions = self
+            .regions
+            .iter()
+            .map(|region| {
+                region
+                    .iter()
+                    .filter(|(row, col)| self.cells[*row][*col].state != CellState::Filled)
+                    .copied()
+                    .collect::<Vec<(usize, usize)>>()
+            })
+            .collect()
+    }
+}
+
+fn adjacencies(width: usize, height: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
+    if row >= height || col >= width {
+        return vec![];
+    }
+
+    let mut adjacencies = vec![];
+
+    if row > 0 {
+        adjacencies.push((row - 1, col));
+        if col > 0 {
+            adjacencies.push((row - 1, col - 1));
+        }
+        if col < width - 1 {
+            adjacencies.push((row - 1, col + 1));
+        }
+    }
+    if row < height - 1 {
+        adjacencies.push((row + 1, col));
+        if col > 0 {
+            adjacencies.push((row + 1, col - 1));
+        }
+        if col < width - 1 {
+            adjacencies.push((row + 1, col + 1));
+        }
+    }
+    if col > 0 {
+        adjacencies.push((row, col - 1));
+    }
+    if col < width - 1 {
+        adjacencies.push((row, col + 1));
+    }
+
+    adjacencies
+}
+
+/// `adjacencies` without the diagonals.
+fn orthogonal_adjacencies(
+    width: usize,
+    height: usize,
+    row: usize,
+    col: usize,
+) -> Vec<(usize, usize)> {
+    let mut adjacencies = adjacencies(width, height, row, col);
+    adjacencies.retain(|&(r, c)| r == row || c == col);
+    adjacencies
+}
+
+fn wrapping_adjacencies(
+    width: usize,
+    height: usize,
+    row: usize,
+    col: usize,
+) -> Vec<(usize, usize)> {
+    if row >= height || col >= width {
+        return vec![];
+    }
+
+    let mut adjacencies = vec![];
+
+    for row_offset in [height - 1, 0, 1] {
+        for col_offset in [width - 1, 0, 1] {
+            let neighbour = ((row + row_offset) % height, (col + col_offset) % width);
+            if neighbour != (row, col) && !adjacencies.contains(&neighbour) {
+                adjacencies.push(neighbour);
+            }
+        }
+    }
+
+    adjacencies
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    /// indexes into the `regions` member of the board struct
+    region: usize,
+    state: CellState,
+}
+
+/// Only blank cells change, and both return whether this one did.
+impl Cell {
+    fn shade(&mut self) -> bool {
+        let blank = self.state == CellState::Blank;
+        if blank {
+            self.state = CellState::Filled;
+        }
+        blank
+    }
+    fn star(&mut self) -> bool {
+        let blank = self.state == CellState::Blank;
+        if blank {
+            self.state = CellState::Star;
+        }
+        blank
+    }
+}
+
+/// Which neighbours of a star have to stay empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdjacencyMode {
+    /// All eight, as in standard Star Battle.
+    #[default]
+    King,
+    /// Only the four that share an edge with it.
+    Orthogonal,
+}
+
+/// More kinds of star or shading may be added, so code outside the crate should
+/// prefer the `is_*` helpers to matching on every variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CellState {
+    Blank,
+    Star,
+    Filled,
+}
+
+impl CellState {
+    /// Undecided: could still turn out to be either a star or empty.
+    pub fn is_blank(&self) -> bool {
+        *self == Self::Blank
+    }
+
+    /// Holds a star, however it got there.
+    pub fn is_star(&self) -> bool {
+        *self == Self::Star
+    }
+
+    /// Known to be empty, so no star can go here.
+    pub fn is_blocked(&self) -> bool {
+        *self == Self::Filled
+    }
+}
+
+impl Display for CellState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", GlyphSet::default().glyph(*self))
+    }
+}
+
+/// The characters a board is drawn with, for `Board::to_string_with_glyphs`.
+/// The default is what `CellState` displays as: `-`, `X` and `#`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlyphSet {
+    /// `None` labels each blank with its region tag, as `Board`'s `Display` does.
+    pub blank: Option<char>,
+    pub star: char,
+    pub filled: char,
+}
+
+impl GlyphSet {
+    /// What `state` is drawn as, with `-` for blanks if `blank` is `None`.
+    pub fn glyph(&self, state: CellState) -> char {
+        match state {
+            CellState::Blank => self.blank.unwrap_or('-'),
+            CellState::Star => self.star,
+            CellState::Filled => self.filled,
+        }
+    }
+}
+
+impl Default for GlyphSet {
+    fn default() -> Self {
+        Self {
+            blank: Some('-'),
+            star: 'X',
+            filled: '#',
+        }
+    }
+}
+
+/// How far a solving attempt got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// Every star is placed and every other cell is shaded.
+    Solved,
+    /// The deductions ran out with blanks still to decide.
+    Stuck,
+    /// The stars placed so far can't be completed into a solution.
+    /// `Board::contradiction` says why.
+    Contradiction,
+    /// The caller asked the solver to stop before it finished.
+    Cancelled,
+}
+
+/// Pass counts from a `solve`, for spotting puzzles that converge slowly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveReport {
+    /// iterations of the outer loop in `solve`, which places forced stars
+    pub outer_passes: usize,
+    /// iterations of the shading fixed point, summed over every time it ran
+    pub inner_passes: usize,
+}
+
+/// One of the lines or regions that must each hold their quota of stars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Region(usize),
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Row(row) => write!(f, "row {row}"),
+            Self::Col(col) => write!(f, "column {col}"),
+            Self::Region(region) => write!(f, "region {region}"),
+        }
+    }
+}
+
+/// What `Board::snapshot` keeps of a board.
+#[derive(PartialEq, Eq)]
+enum Snapshot {
+    Packed(BitBoard),
+    /// for boards too wide to pack
+    Cells(Vec<Vec<Cell>>),
+}
+
+/// Rows or columns, for rules that work the same way along either.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    Rows,
+    Cols,
+}
+
+impl Axis {
+    /// The row or column numbered `index`.
+    fn line(self, index: usize) -> Unit {
+        match self {
+            Self::Rows => Unit::Row(index),
+            Self::Cols => Unit::Col(index),
+        }
+    }
+
+    /// The number of the row or column a cell is in.
+    fn index(self, (row, col): (usize, usize)) -> usize {
+        match self {
+            Self::Rows => row,
+            Self::Cols => col,
+        }
+    }
+}
+
+/// The first and last row or column that `cells` reach, or `None` if there are no cells.
+fn span(cells: &[(usize, usize)], axis: Axis) -> Option<(usize, usize)> {
+    let indices = cells.iter().map(|&cell| axis.index(cell));
+    Some((indices.clone().min()?, indices.max()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_board_sample() -> Board {
         Board::new(
             10,
             10,
             vec![
-                vec![0, 0, 0, 1, 1, 1, 1, 2, 2, 3],
-                vec![0, 0, 0, 1, 2, 2, 1, 2, 2, 3],
-                vec![0, 0, 0, 1, 2, 2, 2, 2, 2, 3],
-                vec![0, 0, 0, 0, 2, 2, 4, 4, 3, 3],
-                vec![5, 5, 4, 4, 4, 4, 4, 4, 3, 3],
-                vec![5, 5, 5, 5, 4, 6, 6, 6, 6, 3],
-                vec![5, 5, 7, 5, 5, 6, 6, 6, 6, 3],
-                vec![8, 8, 7, 7, 6, 6, 6, 6, 6, 3],
-                vec![8, 9, 9, 7, 7, 7, 7, 6, 6, 3],
-                vec![8, 9, 9, 9, 9, 7, 6, 6, 6, 6],
+                vec![0, 0, 0, 1, 1, 1, 1, 2, 2, 3],
+                vec![0, 0, 0, 1, 2, 2, 1, 2, 2, 3],
+                vec![0, 0, 0, 1, 2, 2, 2, 2, 2, 3],
+                vec![0, 0, 0, 0, 2, 2, 4, 4, 3, 3],
+                vec![5, 5, 4, 4, 4, 4, 4, 4, 3, 3],
+                vec![5, 5, 5, 5, 4, 6, 6, 6, 6, 3],
+                vec![5, 5, 7, 5, 5, 6, 6, 6, 6, 3],
+                vec![8, 8, 7, 7, 6, 6, 6, 6, 6, 3],
+                vec![8, 9, 9, 7, 7, 7, 7, 6, 6, 3],
+                vec![8, 9, 9, 9, 9, 7, 6, 6, 6, 6],
+            ],
+        )
+    }
+    fn test_board_stolen_1() -> Board {
+        Board::new(
+            10,
+            10,
+            vec![
+                vec![0, 0, 1, 1, 2, 2, 2, 3, 3, 3],
+                vec![0, 0, 1, 2, 2, 2, 2, 3, 2, 3],
+                vec![0, 0, 1, 1, 1, 1, 2, 2, 2, 3],
+                vec![0, 1, 1, 1, 4, 4, 5, 2, 2, 2],
+                vec![0, 1, 1, 1, 4, 5, 5, 5, 5, 2],
+                vec![0, 6, 6, 6, 4, 4, 4, 4, 2, 2],
+                vec![7, 6, 7, 7, 4, 4, 4, 8, 8, 2],
+                vec![7, 7, 7, 7, 4, 9, 9, 9, 8, 2],
+                vec![7, 7, 7, 8, 8, 8, 8, 8, 8, 2],
+                vec![7, 7, 7, 8, 8, 8, 8, 8, 8, 8],
+            ],
+        )
+    }
+    fn solved_board_stolen_1() -> Board {
+        Board::solved(
+            10,
+            10,
+            vec![
+                vec![1, 3],
+                vec![5, 7],
+                vec![2, 9],
+                vec![4, 6],
+                vec![0, 8],
+                vec![3, 6],
+                vec![1, 9],
+                vec![5, 7],
+                vec![0, 2],
+                vec![4, 8],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_constructor() {
+        let mut board = test_board_stolen_1();
+        assert!(!board.verbose);
+        board.set_verbose(true);
+        assert!(board.verbose);
+    }
+
+    #[test]
+    fn test_one_star_variant() {
+        let regions = vec![
+            vec![0, 0, 1, 1, 1],
+            vec![1, 1, 1, 4, 4],
+            vec![1, 3, 4, 4, 2],
+            vec![3, 3, 4, 4, 2],
+            vec![4, 4, 4, 4, 4],
+        ];
+        let mut board = Board::with_stars_per_unit(5, 5, regions, 1);
+        assert_eq!(board.total_stars(), 5);
+        assert_eq!(board.region_star_quota(4), Some(1));
+        assert!(board.has_unique_solution());
+        assert_eq!(board.solve_with_search(&Mrv), SolveOutcome::Solved);
+        assert_eq!(
+            board.solution_labels(0),
+            ["R0C0", "R1C2", "R2C4", "R3C1", "R4C3"]
+        );
+        assert_eq!(Board::from_compact(&board.to_compact()), Ok(board));
+    }
+
+    #[test]
+    fn test_add_required_stars_slice() {
+        let line = |glyphs: &str| {
+            glyphs
+                .chars()
+                .map(|glyph| Cell {
+                    region: 0,
+                    state: match glyph {
+                        'X' => CellState::Star,
+                        '#' => CellState::Filled,
+                        _ => CellState::Blank,
+                    },
+                })
+                .collect::<Vec<_>>()
+        };
+        let starred = |glyphs: &str, quota: usize, wrap: bool| {
+            let mut cells = line(glyphs);
+            let mut slice = cells.iter_mut().collect::<Vec<_>>();
+            Board::add_required_stars_slice(&mut slice, quota, wrap);
+            cells
+                .iter()
+                .map(|cell| match cell.state {
+                    CellState::Star => 'X',
+                    CellState::Filled => '#',
+                    CellState::Blank => '.',
+                })
+                .collect::<String>()
+        };
+        assert_eq!(starred("#.#.#", 2, false), "#X#X#");
+        assert_eq!(starred("#..#.", 2, false), "#..#X");
+        assert_eq!(starred("...##", 2, false), "X.X##");
+        assert_eq!(starred(".#.#.", 2, false), ".#.#.");
+        assert_eq!(starred(".#.#.", 3, false), "X#X#X");
+        assert_eq!(starred(".#.##", 1, false), ".#.##");
+        assert_eq!(starred("#X##.", 2, false), "#X##X");
+        assert_eq!(starred("##.##", 1, false), "##X##");
+        // the ends of a wrapping line touch
+        assert_eq!(starred(".#.#.", 3, true), ".#.#.");
+        assert_eq!(starred("..#.#", 2, true), "..#X#");
+    }
+
+    #[test]
+    fn test_cell_accessors() {
+        let mut board = Board::build(
+            4,
+            3,
+            vec![vec![0, 0, 1, 1], vec![0, 2, 2, 1], vec![3, 3, 3, 3]],
+        );
+        board.star_cell(0, 0);
+        board.shade_cell(1, 1);
+        assert_eq!(board.dimensions(), (4, 3));
+        assert_eq!(board.cell_state(0, 0), Some(CellState::Star));
+        assert_eq!(board.cell_state(1, 1), Some(CellState::Filled));
+        assert_eq!(board.cell_state(2, 3), Some(CellState::Blank));
+        assert_eq!(board.cell_state(3, 0), None);
+        assert_eq!(board.cell_state(0, 4), None);
+        assert_eq!(board.region_at(1, 1), Some(2));
+        assert_eq!(board.region_at(1, 3), Some(1));
+        assert_eq!(board.region_at(2, 0), Some(3));
+        assert_eq!(board.region_at(0, 4), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.star_cell(0, 0);
+        board.shade_cell(0, 1);
+        board.shade_cell(1, 1);
+        assert_eq!(format!("{board}"), "X # 1 1\n0 # 1 1\n0 0 1 1\n0 0 1 1\n");
+
+        // two-digit tags widen every cell, glyphs included
+        let mut board = Board::build(3, 2, vec![vec![8, 9, 10], vec![8, 11, 10]]);
+        board.star_cell(0, 0);
+        board.shade_cell(1, 2);
+        assert_eq!(format!("{board}"), " X  9 10\n 8 11  #\n");
+
+        let mut written = vec![];
+        board.write_to(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), format!("{board}\n"));
+
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.star_cell(0, 0);
+        board.shade_cell(0, 1);
+        board.shade_cell(1, 1);
+        let fancy = GlyphSet {
+            blank: Some('·'),
+            star: '★',
+            filled: '▓',
+        };
+        assert_eq!(
+            board.to_string_with_glyphs(&fancy),
+            "★ ▓ · ·\n· ▓ · ·\n· · · ·\n· · · ·\n"
+        );
+        assert_eq!(
+            board.to_string_with_glyphs(&GlyphSet::default()),
+            "X # - -\n- # - -\n- - - -\n- - - -\n"
+        );
+        let tagged = GlyphSet {
+            blank: None,
+            ..fancy
+        };
+        assert_eq!(
+            board.to_string_with_glyphs(&tagged),
+            "★ ▓ 1 1\n0 ▓ 1 1\n0 0 1 1\n0 0 1 1\n"
+        );
+        let mut written = vec![];
+        board.write_with_glyphs(&mut written, &tagged).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            format!("{}\n", board.to_string_with_glyphs(&tagged))
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let grid = "0 0 1 1\n0 0 1 1\n2 2 3 3\n2 2 3 3\n";
+        assert_eq!(
+            grid.parse::<Board>(),
+            Ok(Board::build(
+                4,
+                4,
+                vec![
+                    vec![0, 0, 1, 1],
+                    vec![0, 0, 1, 1],
+                    vec![2, 2, 3, 3],
+                    vec![2, 2, 3, 3]
+                ]
+            ))
+        );
+        assert_eq!("0011\n0011\n2233\n2233".parse::<Board>(), grid.parse());
+
+        assert_eq!(
+            "01\n0".parse::<Board>(),
+            Err(BoardError::RaggedRow {
+                row: 1,
+                len: 1,
+                width: 2
+            })
+        );
+        for s in ["", "  \n", "0!\n01", "02\n02", "0a\n0A", "AB\nAB"] {
+            assert!(
+                matches!(s.parse::<Board>(), Err(BoardError::ParseFailure(_))),
+                "{s:?} should not parse"
+            );
+        }
+        assert_eq!(
+            "0A".parse::<Board>(),
+            Err(BoardError::ParseFailure(
+                "`A` is not a region glyph".to_string()
+            ))
+        );
+        assert_eq!(
+            "00\n11".parse::<Board>(),
+            Ok(Board::build(2, 2, vec![vec![0, 0], vec![1, 1]]))
+        );
+        assert_eq!(
+            "01\n10".parse::<Board>(),
+            Err(BoardError::DisconnectedRegion(1))
+        );
+    }
+
+    #[test]
+    fn test_sbn_round_trip() {
+        for board in [test_board_sample(), test_board_stolen_1()] {
+            assert_eq!(Board::from_sbn(&board.to_sbn()), Ok(board));
+        }
+        assert_eq!(
+            test_board_stolen_1().to_sbn(),
+            "10x10:2A2B3C3D2AB4CDCD2A4B3CDA3B2EF3CA3BE4FCA3G4E2CHG2H3E2IC4HE3JIC3H6IC3H7I"
+        );
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.star_cell(0, 0);
+        for (row, col) in [(0, 1), (1, 0), (1, 1)] {
+            board.shade_cell(row, col);
+        }
+        board.regenerate_regions();
+        assert_eq!(
+            board.to_compact(),
+            "4x4;r:0,0,1,1/0,0,1,1/0,0,1,1/0,0,1,1;q:2,2;s:R0C0;f:R0C1,R1C0,R1C1"
+        );
+
+        let mut partial = test_board_stolen_1();
+        partial.enforce_rules();
+        let solved = partial.find_solution().unwrap();
+        let mut wrapped = Board::build(2, 2, vec![vec![0, 1], vec![0, 1]]);
+        wrapped.set_wrap(true);
+        wrapped.set_region_star_counts(vec![1, 1]).unwrap();
+        wrapped.set_adjacency_mode(AdjacencyMode::Orthogonal);
+        for board in [test_board_stolen_1(), partial, solved, wrapped] {
+            let compact = board.to_compact();
+            assert!(!compact.contains('\n'));
+            assert_eq!(Board::from_compact(&compact), Ok(board.clone()));
+            assert_eq!(Board::from_compact(&compact).unwrap().to_compact(), compact);
+        }
+        assert!(Board::from_compact("2x2;wrap;r:0,1/0,1;q:1,1;s:;f:").is_ok());
+        assert!(Board::from_compact("2x2;orthogonal;wrap;r:0,1/0,1;q:1,1;s:;f:").is_err());
+
+        let malformed = [
+            "",
+            "2y2;r:0,1/0,1;q:2,2;s:;f:",
+            "2x2;r:0,1/0,1;q:2,2;s:",
+            "2x2;r:0,1/0,x;q:2,2;s:;f:",
+            "3x2;r:0,1/0,1;q:2,2;s:;f:",
+            "2x2;r:0,1/0,1;q:2,2;s:R2C0;f:",
+            "2x2;r:0,1/0,1;q:2,2;s:;f:C0R0",
+            "2x2;r:0,1/0,1;q:2,2;s:;f:;extra",
+        ];
+        for s in malformed {
+            assert!(
+                matches!(Board::from_compact(s), Err(BoardError::ParseFailure(_))),
+                "{s} should not parse"
+            );
+        }
+        assert_eq!(
+            Board::from_compact("2x2;r:0,1/0,1;q:2;s:;f:"),
+            Err(BoardError::WrongRegionCount {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_sbn_errors() {
+        let malformed = [
+            "AAAA",
+            "2y2:AAAA",
+            "0x2:",
+            "2x2:0AAAA",
+            "2x2:AAA",
+            "2x2:5A",
+            "2x2:AAA3",
+            "2x2:AA!A",
+        ];
+        for s in malformed {
+            assert!(
+                matches!(Board::from_sbn(s), Err(BoardError::ParseFailure(_))),
+                "{s} should not parse"
+            );
+        }
+        assert_eq!(
+            Board::from_sbn("2x2:\n2A\nBB"),
+            Ok(Board::build(2, 2, vec![vec![0, 0], vec![1, 1]]))
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        // let mut board = test_board_sample();
+        // board.solve();
+        // board.print();
+        let mut board = test_board_stolen_1();
+        let solution = solved_board_stolen_1();
+        board.add_solution(solution);
+        // the deductions alone get this far, and the rest takes a guess
+        assert_eq!(board.solve(), SolveOutcome::Stuck);
+        board.print();
+        let mut solved = board.find_solution().unwrap();
+        assert_eq!(solved.solve(), SolveOutcome::Solved);
+        let report = board.report();
+        assert_eq!(board.solve_complete(), SolveOutcome::Solved);
+        assert_eq!(board.cells, solved.cells);
+        assert!(report.outer_passes >= 2);
+        assert!(report.inner_passes > report.outer_passes);
+        // let mut board = test_board_sample();
+        // board.solve();
+        // board.print();
+    }
+
+    #[test]
+    fn test_add_last_region_star() {
+        let regions = vec![
+            vec![0, 0, 0, 0, 2, 2],
+            vec![2, 2, 2, 2, 2, 2],
+            vec![2, 2, 2, 2, 2, 2],
+            vec![1, 1, 1, 1, 2, 2],
+            vec![3, 3, 3, 3, 3, 3],
+            vec![3, 3, 3, 3, 3, 3],
+        ];
+
+        // region 0 is down to a touching pair, so their shared neighbours go
+        let mut board = Board::build(6, 6, regions.clone());
+        board.star_cell(0, 0);
+        board.shade_cell(0, 1);
+        board.add_last_region_star();
+        assert_eq!(board.cells[1][2].state, CellState::Filled);
+        assert_eq!(board.cells[1][3].state, CellState::Filled);
+        assert_eq!(board.cells[1][1].state, CellState::Blank);
+        assert_eq!(board.cells[1][4].state, CellState::Blank);
+        assert_eq!(board.cells[0][2].state, CellState::Blank);
+        assert_eq!(board.cells[0][3].state, CellState::Blank);
+
+        // region 1 has one blank touching its star and one clear of it
+        let mut board = Board::build(6, 6, regions);
+        board.star_cell(3, 0);
+        board.shade_cell(3, 2);
+        board.add_last_region_star();
+        assert_eq!(board.cells[3][1].state, CellState::Filled);
+        assert_eq!(board.cells[3][3].state, CellState::Star);
+    }
+
+    #[test]
+    fn test_board_error_is_an_error() {
+        fn parse(s: &str) -> Result<Board, Box<dyn std::error::Error>> {
+            Ok(Board::from_sbn(s)?)
+        }
+        let error = parse("2x2:AAA").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to parse board: runs cover 3 cells but the grid has 4"
+        );
+        assert_eq!(
+            BoardError::Overfilled(Unit::Col(3)).to_string(),
+            "column 3 has too many stars"
+        );
+    }
+
+    #[test]
+    fn test_solve_region() {
+        let mut board = test_board_stolen_1();
+        board.add_solution(solved_board_stolen_1());
+        assert_eq!(board.solve_region(9), Ok(SolveOutcome::Solved));
+        assert_eq!(board.cells[7][5].state, CellState::Star);
+        assert_eq!(board.cells[7][6].state, CellState::Filled);
+        assert_eq!(board.cells[7][7].state, CellState::Star);
+        assert_eq!(board.cells[6][4].state, CellState::Filled);
+        assert_eq!(board.cells[8][8].state, CellState::Filled);
+        // the row these stars fill isn't this region's business
+        assert_eq!(board.cells[7][0].state, CellState::Blank);
+        assert_eq!(board.cells[0][0].state, CellState::Blank);
+
+        assert_eq!(board.solve_region(0), Ok(SolveOutcome::Stuck));
+        assert_eq!(board.solve_region(10), Err(BoardError::UnknownRegion(10)));
+    }
+
+    #[test]
+    fn test_with_givens() {
+        let regions = test_board_stolen_1()
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.region).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut board = Board::with_givens(10, 10, regions.clone(), &[(0, 1), (0, 3)]).unwrap();
+        assert_eq!(board.cell_state(0, 1), Some(CellState::Star));
+        assert_eq!(board.cell_state(0, 0), Some(CellState::Blank));
+        assert_eq!(board.solve(), SolveOutcome::Solved);
+        assert_eq!(
+            board.solution_labels(0),
+            solved_board_stolen_1().solution_labels(0)
+        );
+
+        let with = |givens: &[(usize, usize)]| Board::with_givens(10, 10, regions.clone(), givens);
+        assert_eq!(
+            with(&[(0, 1), (1, 2)]),
+            Err(BoardError::StarsTouch((0, 1), (1, 2)))
+        );
+        assert_eq!(
+            with(&[(0, 1), (0, 3), (0, 5)]),
+            Err(BoardError::Overfilled(Unit::Row(0)))
+        );
+        assert_eq!(with(&[(10, 0)]), Err(BoardError::OutOfBounds((10, 0))));
+        assert_eq!(
+            Board::with_givens(9, 9, regions.clone(), &[]),
+            Err(BoardError::DimensionMismatch {
+                expected: (9, 9),
+                found: (10, 10),
+            })
+        );
+    }
+
+    #[test]
+    fn test_mark_empty() {
+        let mut board = test_board_stolen_1();
+        board.add_solution(solved_board_stolen_1());
+        assert_eq!(board.mark_empty(0, 0), Ok(()));
+        assert_eq!(board.mark_empty(0, 0), Ok(()));
+        assert_eq!(board.cells[0][0].state, CellState::Filled);
+        assert!(!board.regions[0].contains(&(0, 0)));
+        assert_eq!(
+            board.mark_empty(10, 0),
+            Err(BoardError::OutOfBounds((10, 0)))
+        );
+
+        board.star_cell(0, 1);
+        assert_eq!(board.mark_empty(0, 1), Err(BoardError::CellIsStar((0, 1))));
+        assert_eq!(board.cells[0][1].state, CellState::Star);
+        board.solve();
+    }
+
+    #[test]
+    fn test_find_solution() {
+        let board = test_board_stolen_1();
+        let found = board.find_solution().unwrap();
+        assert_eq!(board, test_board_stolen_1());
+        let solution = solved_board_stolen_1();
+        for row in 0..10 {
+            for col in 0..10 {
+                assert_eq!(found.cells[row][col].state, solution.cells[row][col].state);
+            }
+        }
+
+        // no two rows of four can both hold two stars without touching
+        let board = Board::build(
+            4,
+            4,
+            vec![
+                vec![0, 0, 1, 1],
+                vec![0, 0, 1, 1],
+                vec![2, 2, 3, 3],
+                vec![2, 2, 3, 3],
+            ],
+        );
+        assert_eq!(board.find_solution(), None);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_solve_from_grid() {
+        let grid = test_board_stolen_1().to_string();
+        let solved = solve_from_grid(&grid).unwrap();
+        assert_eq!(solved.lines().count(), 10);
+        assert_eq!(solved.lines().next(), Some("# X # X # # # # # #"));
+        assert!(!solved.contains(|c: char| c.is_ascii_digit()));
+
+        assert_eq!(
+            solve_from_grid("0011\n0011\n2233\n2233"),
+            Err("the puzzle has no solution".to_string())
+        );
+        assert_eq!(
+            solve_from_grid("001\n01"),
+            Err("row 1 has 2 cells but the board is 3 wide".to_string())
+        );
+        assert!(solve_from_grid("00\n0!").is_err());
+        assert!(solve_from_grid("\n \n").is_err());
+    }
+
+    #[test]
+    fn test_region_of_three_blanks() {
+        let three_blank_board = |blanks: [(usize, usize); 3]| {
+            let mut regions = vec![vec![1; 5]; 5];
+            for (row, col) in blanks {
+                regions[row][col] = 0;
+            }
+            // only the blanks matter, so the region needn't hang together
+            Board::build(5, 5, regions)
+        };
+        let states = |board: &Board, cells: [(usize, usize); 3]| {
+            cells.map(|(row, col)| board.cells[row][col].state)
+        };
+
+        // every pair in a tight L touches, so no seating works and nothing is forced
+        let l_shape = [(1, 1), (1, 2), (2, 1)];
+        let mut board = three_blank_board(l_shape);
+        board.add_required_stars_in_region(0, Board::add_star_locally);
+        assert_eq!(states(&board, l_shape), [CellState::Blank; 3]);
+
+        // a straight line only seats stars at both ends
+        let line = [(2, 1), (2, 2), (2, 3)];
+        let mut board = three_blank_board(line);
+        board.add_required_stars_in_region(0, Board::add_star_locally);
+        assert_eq!(
+            states(&board, line),
+            [CellState::Star, CellState::Filled, CellState::Star]
+        );
+
+        // the middle cell touches both ends, which don't touch each other
+        let bend = [(1, 1), (1, 2), (2, 3)];
+        let mut board = three_blank_board(bend);
+        board.add_required_stars_in_region(0, Board::add_star_locally);
+        assert_eq!(
+            states(&board, bend),
+            [CellState::Star, CellState::Filled, CellState::Star]
+        );
+
+        // with no touching pairs, any two of the three will do
+        let spread = [(0, 0), (2, 2), (4, 4)];
+        let mut board = three_blank_board(spread);
+        board.add_required_stars_in_region(0, Board::add_star_locally);
+        assert_eq!(states(&board, spread), [CellState::Blank; 3]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = test_board_stolen_1();
+        let mut after = before.clone();
+        assert_eq!(before.diff(&after), Ok(vec![]));
+
+        after.shade_cell(0, 2);
+        after.star_cell(1, 3);
+        assert_eq!(
+            before.diff(&after),
+            Ok(vec![
+                (0, 2, CellState::Blank, CellState::Filled),
+                (1, 3, CellState::Blank, CellState::Star),
+            ])
+        );
+        assert_eq!(
+            after.diff(&before).unwrap()[1],
+            (1, 3, CellState::Star, CellState::Blank)
+        );
+
+        let small = Board::build(2, 1, vec![vec![0, 0]]);
+        assert_eq!(
+            before.diff(&small),
+            Err(BoardError::DimensionMismatch {
+                expected: (10, 10),
+                found: (2, 1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_solve_with_cancel() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(
+            board.solve_with_cancel(&AtomicBool::new(true)),
+            SolveOutcome::Cancelled
+        );
+        assert_eq!(board, test_board_stolen_1());
+
+        board.add_solution(solved_board_stolen_1());
+        assert_eq!(
+            board.solve_with_cancel(&AtomicBool::new(false)),
+            SolveOutcome::Solved
+        );
+        assert_eq!(
+            board.solution_labels(0),
+            solved_board_stolen_1().solution_labels(0)
+        );
+
+        let mut board = Board::build(
+            4,
+            4,
+            vec![
+                vec![0, 0, 1, 1],
+                vec![0, 0, 1, 1],
+                vec![2, 2, 3, 3],
+                vec![2, 2, 3, 3],
+            ],
+        );
+        assert_eq!(
+            board.solve_with_cancel(&AtomicBool::new(false)),
+            SolveOutcome::Contradiction
+        );
+    }
+
+    #[test]
+    fn test_region_adjacency() {
+        let adjacency = test_board_stolen_1().region_adjacency();
+        assert_eq!(adjacency.len(), 10);
+        assert_eq!(adjacency[0], vec![1, 6, 7]);
+        assert_eq!(adjacency[3], vec![2]);
+        assert_eq!(adjacency[9], vec![4, 8]);
+        for (region, neighbours) in adjacency.iter().enumerate() {
+            for neighbour in neighbours {
+                assert!(adjacency[*neighbour].contains(&region));
+            }
+        }
+
+        let mut board = Board::build(3, 1, vec![vec![0, 1, 2]]);
+        assert_eq!(board.region_adjacency(), vec![vec![1], vec![0, 2], vec![1]]);
+        board.set_wrap(true);
+        assert_eq!(
+            board.region_adjacency(),
+            vec![vec![1, 2], vec![0, 2], vec![0, 1]]
+        );
+
+        // regions meeting only at a corner don't share an edge
+        let board = Board::build(2, 2, vec![vec![0, 1], vec![2, 0]]);
+        assert_eq!(board.region_adjacency(), vec![vec![1, 2], vec![0], vec![0]]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "some_rule left two stars touching")]
+    fn test_debug_check_invariants() {
+        let mut board = test_board_stolen_1();
+        board.star_cell(0, 0);
+        board.debug_check_invariants("some_rule");
+        board.star_cell(1, 1);
+        board.debug_check_invariants("some_rule");
+    }
+
+    #[test]
+    fn test_place_star() {
+        let mut board = test_board_stolen_1();
+        assert!(board.place_star(0, 0));
+        assert!(!board.place_star(0, 0));
+        // touching the first star
+        assert!(!board.place_star(1, 1));
+        assert_eq!(board.cells[1][1].state, CellState::Blank);
+        assert!(board.place_star(0, 2));
+        // row 0 has its two stars already
+        assert!(!board.place_star(0, 9));
+        assert_eq!(board.cells[0][9].state, CellState::Blank);
+        assert!(board.is_legal());
+    }
+
+    #[test]
+    fn test_region_star_counts() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(
+            board.set_region_star_counts(vec![1; 3]),
+            Err(BoardError::WrongRegionCount {
+                expected: 10,
+                found: 3
+            })
+        );
+
+        let mut counts = vec![2; 10];
+        counts[9] = 1;
+        board.set_region_star_counts(counts).unwrap();
+        // a one-star region is full as soon as it has a star
+        board.star_cell(7, 6);
+        board.blackout_regions();
+        assert_eq!(board.cells[7][5].state, CellState::Filled);
+        assert_eq!(board.cells[7][7].state, CellState::Filled);
+        assert_eq!(board.region_outcome(9), SolveOutcome::Solved);
+
+        // and a one-star region down to one blank gets it
+        let mut board = Board::build(4, 1, vec![vec![0, 0, 1, 1]]);
+        board.set_region_star_counts(vec![1, 1]).unwrap();
+        board.shade_cell(0, 0);
+        board.add_required_stars_in_region(0, Board::add_star_locally);
+        assert_eq!(board.cells[0][1].state, CellState::Star);
+        board.add_required_stars_in_region(1, Board::add_star_locally);
+        assert_eq!(board.cells[0][3].state, CellState::Star);
+    }
+
+    #[test]
+    fn test_line_placements() {
+        let board = test_board_stolen_1();
+        // two stars in a line of n cells need a gap, leaving (n - 1) choose 2 seatings
+        for (len, expected) in [(2, 0), (3, 1), (4, 3), (5, 6), (6, 10)] {
+            let line = (0..len).map(|col| (4, col)).collect::<Vec<_>>();
+            let placements = board.line_placements(&line, 2);
+            assert_eq!(placements.len(), expected, "line of {len}");
+            assert_eq!(board.has_seating(&line, 2), expected > 0, "line of {len}");
+            for placement in placements {
+                let [(_, first), (_, second)] = placement[..] else {
+                    panic!("expected two stars, got {placement:?}");
+                };
+                assert!(second > first + 1);
+            }
+        }
+        assert_eq!(
+            board.line_placements(&[(0, 3), (1, 3), (2, 3)], 2),
+            vec![vec![(0, 3), (2, 3)]]
+        );
+    }
+
+    #[test]
+    fn test_enforce_rules_reports_change() {
+        let mut board = test_board_stolen_1();
+        assert!(board.star_cell(0, 0));
+        assert!(!board.shade_cell(0, 0));
+        assert!(board.enforce_rules());
+        assert_eq!(board.cells[1][1].state, CellState::Filled);
+        assert!(board.changed);
+        board.changed = false;
+        assert!(!board.enforce_rules());
+        assert!(!board.changed);
+
+        // a star rule calling in keeps the change it made flagged
+        board.changed = true;
+        assert!(!board.enforce_rules());
+        assert!(board.changed);
+    }
+
+    #[test]
+    fn test_candidate_counts() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        assert_eq!(board.candidate_counts(), vec![vec![3; 4]; 4]);
+
+        board.star_cell(0, 0);
+        board.shade_cell(3, 3);
+        let counts = board.candidate_counts();
+        assert_eq!(counts[0][0], 0);
+        assert_eq!(counts[1][1], 0);
+        assert_eq!(counts[3][3], 0);
+        assert_eq!(counts[0][2], 3);
+        // row 0 and column 0 still want a second star, and so does region 0
+        assert_eq!(counts[2][0], 3);
+
+        board.star_cell(2, 0);
+        let counts = board.candidate_counts();
+        // region 0 is full, so none of its cells count
+        assert!(counts.iter().all(|row| row[0] == 0 && row[1] == 0));
+        assert_eq!(counts[2][3], 3);
+    }
+
+    #[test]
+    fn test_search_with_rules_off() {
+        // with rows, columns and regions off, nothing shades the cells left
+        // over once every unit has its stars, so the search has to
+        let mut board = test_board_stolen_1();
+        board.solve_with_rules(RuleSet::empty());
+        assert_eq!(board.solve_complete(), SolveOutcome::Solved);
+        assert!(board.is_solved());
+        assert_eq!(board.remaining_blanks(), 0);
+
+        let mut bare = test_board_sample();
+        bare.solve_bounded(1);
+        assert!(bare.remaining_blanks() > 0);
+        bare.rules = RuleSet::empty();
+        assert_eq!(bare.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn test_solve_with_config() {
+        let mut board = test_board_stolen_1();
+        board.solve_with_config(SolveConfig {
+            rules: RuleSet::empty(),
+        });
+        assert_eq!(board, test_board_stolen_1());
+
+        let mut board = test_board_stolen_1();
+        board.star_cell(0, 0);
+        board.solve_with_config(SolveConfig {
+            rules: RuleSet::ADJACENCY,
+        });
+        assert_eq!(board.cells[1][1].state, CellState::Filled);
+        // the rest of row 0 would go once it had a second star, but rows are off
+        assert_eq!(board.cells[0][5].state, CellState::Blank);
+
+        let rules = RuleSet::all() - RuleSet::SMALL_REGION;
+        assert!(rules.contains(RuleSet::ROWS | RuleSet::COLS));
+        assert!(!rules.contains(RuleSet::SMALL_REGION));
+        assert_eq!(rules | RuleSet::SMALL_REGION, RuleSet::default());
+
+        let basic = RuleSet::all() - RuleSet::SMALL_REGION - RuleSet::CONTIGUITY;
+        let mut board = test_board_stolen_1();
+        let mut expected = test_board_stolen_1();
+        assert_eq!(
+            board.solve_with_rules(basic),
+            expected.solve_with_config(SolveConfig { rules: basic })
+        );
+        assert_eq!(board, expected);
+        assert_eq!(board.rules, basic);
+    }
+
+    #[test]
+    fn test_enforce_rules_cascades() {
+        let mut board = test_board_stolen_1();
+        board.add_solution(solved_board_stolen_1());
+        for (row, col) in [(0, 1), (0, 3), (1, 5), (1, 7)] {
+            board.star_cell(row, col);
+        }
+        assert!(board.enforce_rules());
+        // each pass's shading gives the other rules something new to work with,
+        // so it takes several passes to settle
+        assert!(board.report.inner_passes >= 3);
+        assert!(!board.enforce_rules());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut wide = Board::build(129, 1, vec![vec![0; 129]]);
+        assert!(BitBoard::new(&wide).is_none());
+
+        for board in [&mut Board::build(4, 4, vec![vec![0; 4]; 4]), &mut wide] {
+            let before = board.snapshot();
+            assert!(board.clone().snapshot() == before);
+            board.shade_cell(0, 3);
+            assert!(board.snapshot() != before);
+        }
+    }
+
+    #[test]
+    fn test_touching_blanks_are_not_starred() {
+        // a region two cells wide leaves its rows only touching pairs of blanks
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.shade_cell(0, 0);
+        board.shade_cell(0, 1);
+        board.add_required_stars_rows();
+        assert_eq!(board.cells[0][2].state, CellState::Blank);
+        assert_eq!(board.cells[0][3].state, CellState::Blank);
+        assert!(board.is_contradictory());
+
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.shade_cell(0, 0);
+        board.shade_cell(0, 1);
+        assert!(board.find_solution().is_none());
+    }
+
+    #[test]
+    fn test_try_from_grid() {
+        let squares = vec![
+            vec![0, 0, 1, 1],
+            vec![0, 0, 1, 1],
+            vec![2, 2, 3, 3],
+            vec![2, 2, 3, 3],
+        ];
+        let board = Board::try_from(squares.clone()).unwrap();
+        assert_eq!((board.width, board.height), (4, 4));
+        assert_eq!(board, Board::build(4, 4, squares.clone()));
+        assert_eq!(board.total_stars(), 8);
+        // each square fits only one star, which `try_new` won't have
+        assert_eq!(
+            Board::try_new(4, 4, squares),
+            Err(BoardError::RegionTooSmall(0))
+        );
+
+        assert_eq!(
+            Board::try_from(vec![vec![0, 0, 1, 1]; 2]),
+            Err(BoardError::NotSquare {
+                width: 4,
+                height: 2
+            })
+        );
+        // two regions can't hold the eight stars four rows need
+        assert_eq!(
+            Board::try_from(vec![vec![0, 0, 1, 1]; 4]),
+            Err(BoardError::WrongRegionCount {
+                expected: 4,
+                found: 2
+            })
+        );
+
+        assert_eq!(
+            Board::try_from(vec![vec![0, 0, 1], vec![0, 1]]),
+            Err(BoardError::RaggedRow {
+                row: 1,
+                len: 2,
+                width: 3
+            })
+        );
+        assert_eq!(
+            Board::try_from(vec![vec![0, 1, 0], vec![0, 1, 1], vec![2, 2, 2]]),
+            Err(BoardError::DisconnectedRegion(0))
+        );
+        // diagonal contact doesn't join a region up
+        assert_eq!(
+            Board::try_from(vec![vec![0, 1], vec![1, 0]]),
+            Err(BoardError::DisconnectedRegion(1))
+        );
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let board = test_board_stolen_1();
+        assert_eq!(board.count_solutions(2), 1);
+        assert!(board.has_unique_solution());
+        assert_eq!(board.count_solutions(0), 0);
+
+        let board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        assert_eq!(board.count_solutions(2), 0);
+        assert!(!board.has_unique_solution());
+
+        // with every row its own region, an 8x8 board can be filled in two ways
+        let stripes = Board::build(8, 8, (0..8).map(|row| vec![row; 8]).collect());
+        assert_eq!(stripes.count_solutions(5), 2);
+        assert_eq!(stripes.count_solutions(1), 1);
+        assert!(!stripes.has_unique_solution());
+    }
+
+    #[test]
+    fn test_sparse_region_tags() {
+        // the same puzzle with tags that skip around, so a region's index
+        // only lines up with its tag if nothing gets renumbered along the way
+        let retag = |board: Board| {
+            let regions = board
+                .cells
+                .iter()
+                .map(|row| row.iter().map(|cell| 3 * cell.region + 1).collect())
+                .collect();
+            Board::build(board.width, board.height, regions)
+        };
+        let mut board = retag(test_board_stolen_1());
+        assert_eq!(board.regions.len(), 29);
+        assert!(board.regions[0].is_empty());
+        assert_eq!(board.region_star_counts[0], 0);
+        board.add_solution(solved_board_stolen_1());
+
+        // placing a star enforces the rules, which can place more stars in turn,
+        // and it should all come out the same as with the original tags
+        board.solve();
+        let mut original = test_board_stolen_1();
+        original.solve();
+        assert_eq!(board.diff(&original), Ok(vec![]));
+        for (tag, region) in board.regions.iter().enumerate() {
+            assert!(region
+                .iter()
+                .all(|&(row, col)| board.cells[row][col].region == tag));
+            if tag % 3 == 1 {
+                assert_eq!(region, &original.regions[tag / 3], "region {tag}");
+            } else {
+                assert!(region.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_region_quota_queries() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 3, 3]; 4]);
+        assert_eq!(board.num_regions(), 2);
+        assert_eq!(board.region_star_quota(0), Some(2));
+        assert_eq!(board.region_star_quota(1), None);
+        assert_eq!(board.region_star_quota(4), None);
+
+        assert_eq!(
+            board.validate_star_totals(),
+            Err(BoardError::InconsistentStarTotals {
+                rows: 8,
+                cols: 8,
+                regions: 4
+            })
+        );
+
+        board.set_region_star_counts(vec![1, 5, 5, 3]).unwrap();
+        assert_eq!(board.region_star_quota(0), Some(1));
+        assert_eq!(board.region_star_quota(2), None);
+        assert_eq!(board.region_star_quota(3), Some(3));
+        assert!(board.validate_star_totals().is_err());
+
+        // two regions of four stars each make up for there being only two
+        board.set_region_star_counts(vec![4, 0, 0, 4]).unwrap();
+        assert_eq!(board.validate_star_totals(), Ok(()));
+        assert_eq!(test_board_stolen_1().validate_star_totals(), Ok(()));
+
+        // and the region rules solve a board with fewer regions than rows
+        board.set_stars_per_unit(1);
+        board.set_region_star_counts(vec![2, 0, 0, 2]).unwrap();
+        assert_eq!(board.validate_star_totals(), Ok(()));
+        assert_eq!(board.solve_complete(), SolveOutcome::Solved);
+        assert!(board.is_solved());
+        assert_eq!(test_board_stolen_1().num_regions(), 10);
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut board = test_board_stolen_1();
+        board.add_solution(solved_board_stolen_1());
+        let explanation = board.explain();
+        let steps = explanation.lines().collect::<Vec<_>>();
+        assert_eq!(
+            steps[0],
+            "Step 1: A small empty region has only a few ways to fit its stars, \
+             and they all rule these cells out, so R2C9, R5C3, R5C4, R5C5, R6C1, R6C3, \
+             R6C5, R7C1, R7C3, R7C4, R7C5, R7C6, R7C7, R7C8, R7C9, R8C5, R8C7, R8C9, \
+             R9C5, R9C6, R9C7, R9C8 and R9C9 are empty."
+        );
+        assert_eq!(
+            steps[1],
+            "Step 2: However a line's remaining stars are seated, they touch these cells \
+             in the lines beside it, so R6C2, R6C9, R6C10, R8C1, R8C2, R8C3 and R8C10 are empty."
+        );
+        assert_eq!(
+            steps[11],
+            "Step 12: No further logic available; trying R4C7 as a star."
+        );
+        assert_eq!(
+            steps.last(),
+            Some(&"Step 30: Every star is in place, so the puzzle is solved.")
+        );
+        assert_eq!(board.outcome(), SolveOutcome::Solved);
+
+        let mut again = test_board_stolen_1();
+        assert_eq!(again.explain(), explanation);
+    }
+
+    #[test]
+    fn test_solve_with_trace() {
+        let mut board = test_board_stolen_1();
+        let trace = board.solve_with_trace();
+        assert_eq!(
+            trace[0],
+            Deduction {
+                row: 1,
+                col: 8,
+                new_state: CellState::Filled,
+                rule: RuleKind::SmallRegion,
+            }
+        );
+        assert!(trace.iter().any(|step| step.new_state == CellState::Star));
+
+        // the trace accounts for every cell the deductions decided, and only those
+        let solution = solved_board_stolen_1();
+        let mut replayed = test_board_stolen_1();
+        for step in &trace {
+            assert_eq!(step.new_state, solution.cells[step.row][step.col].state);
+            replayed.set_state(step.row, step.col, step.new_state);
+        }
+        assert_eq!(replayed.cells, board.cells);
+        assert!(board.solve_with_trace().is_empty());
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let given_rows = |rows: usize| {
+            let mut board = test_board_stolen_1();
+            let solution = solved_board_stolen_1();
+            for row in 0..rows {
+                for col in 0..board.width {
+                    if solution.cells[row][col].state == CellState::Star {
+                        board.set_state(row, col, CellState::Star);
+                    }
+                }
+            }
+            board
+        };
+
+        let board = given_rows(0);
+        let difficulty = board.difficulty();
+        assert_eq!(difficulty.rating, Rating::Hard);
+        assert_eq!(difficulty.rule_counts.get(&RuleKind::Confinement), Some(&3));
+        assert_eq!(board, given_rows(0));
+
+        let difficulty = given_rows(1).difficulty();
+        assert_eq!(difficulty.rating, Rating::Medium);
+        assert!(difficulty.rule_counts.contains_key(&RuleKind::Contiguity));
+
+        let difficulty = given_rows(9).difficulty();
+        assert_eq!(difficulty.rating, Rating::Easy);
+        assert_eq!(difficulty.rule_counts.values().sum::<usize>(), 3);
+        assert!(Rating::Easy < Rating::Medium && Rating::Medium < Rating::Hard);
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut board = test_board_stolen_1();
+        let snapshots = board.steps().collect::<Vec<_>>();
+        // `explain` has to guess at its twelfth step
+        assert_eq!(snapshots.len(), 11);
+        let mut previous = test_board_stolen_1();
+        for snapshot in &snapshots {
+            assert_ne!(snapshot.cells, previous.cells);
+            assert!(!snapshot.is_contradictory());
+            previous = snapshot.clone();
+        }
+        assert_eq!(snapshots.last(), Some(&board));
+        assert_eq!(board.steps().next(), None);
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(
+            board.hint(),
+            Some(Hint {
+                row: 1,
+                col: 8,
+                state: CellState::Filled,
+                reason: RuleKind::SmallRegion,
+            })
+        );
+        assert_eq!(board, test_board_stolen_1());
+
+        let solution = solved_board_stolen_1();
+        while let Some(hint) = board.hint() {
+            assert_eq!(hint.state, solution.cells[hint.row][hint.col].state);
+            board.steps().next();
+        }
+        assert_eq!(board.outcome(), SolveOutcome::Stuck);
+    }
+
+    #[test]
+    fn test_peek_next_rule() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(
+            board.peek_next_rule(),
+            Some("eliminate_middle_of_small_empty_regions")
+        );
+        assert_eq!(board, test_board_stolen_1());
+
+        board.star_cell(0, 1);
+        assert_eq!(board.peek_next_rule(), Some("blackout_star_adjacencies"));
+        board.rules = RuleSet::empty();
+        assert_eq!(board.peek_next_rule(), None);
+
+        // touching stars leave nothing to deduce, as `explain` finds
+        let mut board = test_board_stolen_1();
+        board.star_cell(0, 1);
+        board.star_cell(1, 2);
+        assert_eq!(board.peek_next_rule(), None);
+        assert_eq!(board.hint(), None);
+    }
+
+    #[test]
+    fn test_validate_regions() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.validate_regions(), Ok(()));
+
+        // cut the corner off region 0 and hand it to region 8, far away
+        board.cells[0][0].region = 8;
+        assert_eq!(board.validate_regions(), Err(vec![8]));
+        // and region 3 turns up in pieces earlier in the scan
+        board.cells[0][4].region = 3;
+        assert_eq!(board.validate_regions(), Err(vec![3, 8]));
+    }
+
+    #[test]
+    fn test_try_new() {
+        let regions = test_board_stolen_1()
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.region).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Board::try_new(10, 10, regions.clone()),
+            Ok(test_board_stolen_1())
+        );
+        assert_eq!(
+            Board::try_new(10, 9, regions.clone()),
+            Err(BoardError::DimensionMismatch {
+                expected: (10, 9),
+                found: (10, 10)
+            })
+        );
+        let mut ragged = regions.clone();
+        ragged[3].pop();
+        assert!(matches!(
+            Board::try_new(10, 10, ragged),
+            Err(BoardError::RaggedRow { row: 3, .. })
+        ));
+
+        // tags 0, 2, 3, 4 make four regions for four rows, but skip 1
+        let skipping = vec![
+            vec![0, 0, 2, 2],
+            vec![0, 0, 2, 2],
+            vec![3, 3, 4, 4],
+            vec![3, 3, 4, 4],
+        ];
+        assert!(Board::try_from(skipping.clone()).is_ok());
+        assert_eq!(
+            Board::try_new(4, 4, skipping),
+            Err(BoardError::MissingRegion(1))
+        );
+
+        // a 1x3 region fits two stars apart, a 2x2 one doesn't
+        let cramped = vec![
+            vec![0, 0, 0, 1, 1],
+            vec![2, 2, 1, 1, 1],
+            vec![2, 2, 3, 3, 3],
+            vec![4, 4, 4, 4, 3],
+            vec![4, 4, 4, 4, 4],
+        ];
+        assert_eq!(
+            Board::try_new(5, 5, cramped.clone()),
+            Err(BoardError::RegionTooSmall(2))
+        );
+        let board = Board::with_stars_per_unit(5, 5, cramped, 1);
+        assert_eq!(board.region_star_quota(2), Some(1));
+    }
+
+    #[test]
+    fn test_from_char_grid() {
+        let letters = "
+            AABB
+            AABB
+            ccDD
+            ccDD
+        ";
+        let board = Board::from_char_grid(letters).unwrap();
+        assert_eq!(board, "0011\n0011\n2233\n2233".parse().unwrap());
+        assert_eq!(
+            Board::from_char_grid("x y y y\nx x y z\nw x z z\nw w w z"),
+            Board::try_from(vec![
+                vec![0, 1, 1, 1],
+                vec![0, 0, 1, 2],
+                vec![3, 0, 2, 2],
+                vec![3, 3, 3, 2],
+            ])
+        );
+        assert_eq!(
+            Board::from_char_grid("A,A,B,B\nA,A,B,B\nC,C,D,D\nC,C,D,D"),
+            Ok(board)
+        );
+
+        assert_eq!(
+            Board::from_char_grid("AAB\nAB\nCCC"),
+            Err(BoardError::RaggedRow {
+                row: 1,
+                len: 2,
+                width: 3
+            })
+        );
+        // upper and lower case are different regions
+        assert_eq!(
+            Board::from_char_grid("AaB\nAaB\nAaB"),
+            Board::try_from(vec![vec![0, 1, 2]; 3])
+        );
+        assert!(Board::from_char_grid(" \n").is_err());
+    }
+
+    #[test]
+    fn test_from_flat() {
+        let flat = [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 3, 3, 2, 2, 3, 3];
+        let board = Board::from_flat(4, &flat).unwrap();
+        assert_eq!(
+            board,
+            Board::try_from(vec![
+                vec![0, 0, 1, 1],
+                vec![0, 0, 1, 1],
+                vec![2, 2, 3, 3],
+                vec![2, 2, 3, 3]
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            Board::from_flat(3, &flat),
+            Err(BoardError::WrongCellCount {
+                expected: 18,
+                found: 16
+            })
+        );
+        assert_eq!(
+            Board::from_flat(0, &flat),
+            Err(BoardError::WrongCellCount {
+                expected: 0,
+                found: 16
+            })
+        );
+        assert_eq!(
+            Board::from_flat(8, &flat),
+            Err(BoardError::NotSquare {
+                width: 8,
+                height: 2
+            })
+        );
+        assert_eq!(
+            Board::from_flat(2, &[0, 1, 1, 0]),
+            Err(BoardError::DisconnectedRegion(1))
+        );
+    }
+
+    #[test]
+    fn test_board_hash() {
+        let hash = |board: &Board| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+        let board = test_board_stolen_1();
+        assert_eq!(hash(&board), hash(&test_board_stolen_1()));
+
+        let mut annotated = test_board_stolen_1();
+        annotated.add_solution(solved_board_stolen_1());
+        annotated.report.outer_passes = 3;
+        annotated.changed = true;
+        let star = Move {
+            row: 0,
+            col: 1,
+            from: CellState::Blank,
+            to: CellState::Star,
+        };
+        annotated.apply_move(star).unwrap();
+        annotated.undo().unwrap();
+        assert_eq!(annotated, board);
+        assert_eq!(hash(&annotated), hash(&board));
+
+        // the solution only exists in test builds, and must never count
+        let mut answered = test_board_stolen_1();
+        answered.add_solution(test_board_stolen_1().find_solution().unwrap());
+        assert!(answered.solution.is_some());
+        assert_eq!(answered, board);
+
+        let mut wrapped = test_board_stolen_1();
+        wrapped.set_wrap(true);
+        assert_ne!(wrapped, board);
+        assert!(wrapped.same_cells(&board));
+
+        let mut starred = test_board_stolen_1();
+        starred.star_cell(0, 1);
+        assert_ne!(hash(&starred), hash(&board));
+        assert!(!starred.same_cells(&board));
+        let states = HashSet::from([board, annotated, starred]);
+        assert_eq!(states.len(), 2);
+    }
+
+    #[test]
+    fn test_blackout_next_to_contiguity() {
+        let line_board = |blanks: &[usize]| {
+            let mut board = Board::build(6, 7, vec![vec![0; 6]; 7]);
+            for col in (0..6).filter(|col| !blanks.contains(col)) {
+                board.shade_cell(3, col);
+            }
+            board.blackout_next_to_contiguity();
+            board
+        };
+        let shaded = |board: &Board, row: usize| {
+            (0..6)
+                .filter(|&col| board.cells[row][col].state == CellState::Filled)
+                .collect::<Vec<_>>()
+        };
+
+        // the stars go at 0 and 3, or 0 and 4
+        let board = line_board(&[0, 3, 4]);
+        assert_eq!(shaded(&board, 2), vec![0, 1, 3, 4]);
+        assert_eq!(shaded(&board, 4), vec![0, 1, 3, 4]);
+
+        // 2 and 4 is a seating too, which leaves the first column alone
+        let board = line_board(&[0, 1, 2, 4]);
+        let row = shaded(&board, 2);
+        assert!(row.contains(&1) && row.contains(&3) && !row.contains(&0));
+
+        // a single star's worth of adjacent blanks shades beside both
+        let mut board = Board::build(6, 7, vec![vec![0; 6]; 7]);
+        board.star_cell(3, 0);
+        for col in [1, 2, 5] {
+            board.shade_cell(3, col);
+        }
+        board.blackout_next_to_contiguity();
+        assert_eq!(shaded(&board, 2), vec![3, 4]);
+
+        // five blanks in a row still pin both stars near enough to either end
+        let board = line_board(&[0, 1, 2, 3, 4]);
+        for row in [2, 4] {
+            let shaded = shaded(&board, row);
+            assert!(shaded.contains(&1) && shaded.contains(&3));
+            assert!(![0, 2, 4].iter().any(|col| shaded.contains(col)));
+        }
+
+        // and columns get the same treatment as rows
+        let mut board = Board::build(7, 6, vec![vec![0; 7]; 6]);
+        for row in [1, 2, 5] {
+            board.shade_cell(row, 3);
+        }
+        board.blackout_next_to_contiguity();
+        let shaded_col = |col: usize| {
+            (0..6)
+                .filter(|&row| board.cells[row][col].state == CellState::Filled)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shaded_col(2), vec![0, 1, 3, 4]);
+        assert_eq!(shaded_col(4), vec![0, 1, 3, 4]);
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn test_generate() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let board = Board::generate(8, 8, &mut StdRng::seed_from_u64(7));
+        assert_eq!(board.dimensions(), (8, 8));
+        assert_eq!(board.num_regions(), 8);
+        assert!(board
+            .cells
+            .iter()
+            .flatten()
+            .all(|cell| cell.state == CellState::Blank));
+        assert!(board.has_unique_solution());
+        assert_eq!(board, Board::generate(8, 8, &mut StdRng::seed_from_u64(7)));
+
+        let mut solved = board.clone();
+        assert_eq!(solved.solve_complete(), SolveOutcome::Solved);
+        assert_eq!(solved.solved_regions().len(), 8);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut board = test_board_stolen_1();
+        board.solve();
+        board.set_adjacency_mode(AdjacencyMode::Orthogonal);
+        assert!(board
+            .cells
+            .iter()
+            .flatten()
+            .any(|cell| cell.state.is_blank()));
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(!json.contains("regions\""));
+        let restored = serde_json::from_str::<Board>(&json).unwrap();
+        assert_eq!(restored, board);
+
+        // a region map the solver couldn't work with is turned away
+        let broken = json.replacen("\"region\":0", "\"region\":9", 1);
+        assert!(serde_json::from_str::<Board>(&broken).is_err());
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_board() {
+        let regions = test_board_stolen_1()
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.region)
+            .collect::<Vec<_>>();
+        let mut board = WasmBoard::new(10, &regions).unwrap();
+        let mut expected = test_board_stolen_1();
+        expected.solve_complete();
+        assert_eq!(board.solve(), Ok(expected.to_string()));
+        assert!(WasmBoard::new(0, &regions).is_err());
+        assert!(WasmBoard::new(3, &regions).is_err());
+
+        let mut squares =
+            WasmBoard::new(4, &[0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 3, 3, 2, 2, 3, 3]).unwrap();
+        assert!(squares.solve().is_err());
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_board_json() {
+        let mut board =
+            WasmBoard::from_json("[[0, 0, 1, 1], [0, 0, 1, 1], [2, 2, 3, 3], [2, 2, 3, 3]]")
+                .unwrap();
+        let json = board.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<Board>(&json).unwrap(),
+            "0011\n0011\n2233\n2233".parse().unwrap()
+        );
+        assert!(board.solve().is_err());
+        assert_eq!(board.to_json().unwrap(), json);
+        assert!(WasmBoard::from_json("[[0, 0], [1]]").is_err());
+        assert!(WasmBoard::from_json("not json").is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_solve_all() {
+        let squares = "0011\n0011\n2233\n2233".parse::<Board>().unwrap();
+        let boards = vec![test_board_stolen_1(), squares, test_board_sample()];
+        let solved = solve_all(boards.clone());
+        assert_eq!(solved.len(), boards.len());
+        for (board, (result, outcome)) in boards.into_iter().zip(solved) {
+            let mut expected = board;
+            assert_eq!(outcome, expected.solve_complete());
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn test_ffi() {
+        use ffi::*;
+
+        let regions = [0, 0, 1, 1];
+        let board = unsafe { tnt_board_from_flat(2, 2, regions.as_ptr()) };
+        assert!(!board.is_null());
+        unsafe { tnt_board_free(board) };
+        let disconnected = [0, 1, 1, 0];
+        assert!(unsafe { tnt_board_from_flat(2, 2, disconnected.as_ptr()) }.is_null());
+        assert!(unsafe { tnt_board_from_flat(2, 2, std::ptr::null()) }.is_null());
+        assert_eq!(unsafe { tnt_solve(std::ptr::null_mut()) }, TNT_ERROR);
+
+        let regions = test_board_stolen_1()
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.region)
+            .collect::<Vec<_>>();
+        let board = unsafe { tnt_board_from_flat(10, 10, regions.as_ptr()) };
+        assert_eq!(unsafe { tnt_solve(board) }, 1);
+        let mut stars = [0; 4];
+        let total = unsafe { tnt_get_stars(board, stars.as_mut_ptr(), stars.len()) };
+        let expected = unsafe { &*board }
+            .cells
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_index, cell)| cell.state == CellState::Star)
+            .map(|(index, _cell)| index)
+            .collect::<Vec<_>>();
+        assert_eq!(total, expected.len());
+        assert_eq!(stars[..], expected[..4]);
+        unsafe { tnt_board_free(board) };
+    }
+
+    #[test]
+    fn test_blackout_beside_confined_regions() {
+        let mut board = Board::build(
+            6,
+            4,
+            vec![
+                vec![0, 0, 0, 1, 1, 1],
+                vec![0, 0, 0, 1, 1, 1],
+                vec![2, 2, 2, 3, 3, 3],
+                vec![2, 2, 2, 3, 3, 3],
+            ],
+        );
+        // region 0 has a star, and its only other blanks are in row 1
+        board.star_cell(0, 0);
+        for col in 1..3 {
+            board.shade_cell(0, col);
+        }
+        board.blackout_beside_confined_regions();
+        // row 1 needs two stars and region 0 only one, so nothing follows yet
+        assert!(board.cells[1]
+            .iter()
+            .all(|cell| cell.state == CellState::Blank));
+
+        // once row 1 is down to one star too, it has to come from region 0
+        board.star_cell(1, 5);
+        board.blackout_beside_confined_regions();
+        assert_eq!(board.cells[1][3].state, CellState::Filled);
+        assert_eq!(board.cells[1][4].state, CellState::Filled);
+        assert_eq!(board.cells[1][1].state, CellState::Blank);
+        assert_eq!(board.cells[1][5].state, CellState::Star);
+    }
+
+    #[test]
+    fn test_blackout_full_2x2_blocks() {
+        let mut board = Board::build(
+            4,
+            4,
+            vec![
+                vec![0, 0, 1, 1],
+                vec![0, 1, 1, 1],
+                vec![2, 2, 3, 3],
+                vec![2, 2, 3, 3],
+            ],
+        );
+        board.region_star_counts[0] = 1;
+        board.blackout_full_2x2_blocks();
+        // region 0's star touches the corner that completes its L, wherever it goes
+        assert_eq!(board.cells[1][1].state, CellState::Filled);
+        assert_eq!(
+            board
+                .cells
+                .iter()
+                .flatten()
+                .filter(|cell| cell.state.is_blocked())
+                .count(),
+            1
+        );
+
+        // one that owes two stars, or whose blanks spread past one block, is left alone
+        let mut board = Board::build(
+            4,
+            4,
+            vec![
+                vec![0, 0, 1, 1],
+                vec![0, 1, 1, 1],
+                vec![2, 2, 3, 3],
+                vec![2, 2, 3, 3],
+            ],
+        );
+        board.region_star_counts[1] = 1;
+        board.blackout_full_2x2_blocks();
+        assert!(board
+            .cells
+            .iter()
+            .flatten()
+            .all(|cell| cell.state.is_blank()));
+    }
+
+    #[test]
+    fn test_blackout_region_pigeonhole() {
+        let banded = || {
+            Board::build(
+                6,
+                6,
+                vec![
+                    vec![0, 0, 0, 1, 1, 2],
+                    vec![0, 0, 0, 1, 1, 2],
+                    vec![3, 3, 3, 3, 2, 2],
+                    vec![3, 4, 4, 4, 4, 2],
+                    vec![5, 5, 5, 4, 4, 2],
+                    vec![5, 5, 5, 5, 5, 5],
+                ],
+            )
+        };
+        let mut board = banded();
+        board.blackout_region_row_pigeonhole();
+        // regions 0 and 1 owe the top two rows all four of their stars
+        assert_eq!(board.cells[0][5].state, CellState::Filled);
+        assert_eq!(board.cells[1][5].state, CellState::Filled);
+        // which leaves region 2 inside the top five rows too, so region 5's
+        // stars are both in the bottom one
+        assert_eq!(
+            board
+                .cells
+                .iter()
+                .flatten()
+                .filter(|cell| cell.state.is_blocked())
+                .count(),
+            5
+        );
+        assert!((0..3).all(|col| board.cells[4][col].state.is_blocked()));
+
+        // the same board on its side, banded by columns
+        let mut turned = banded().rotated_90().mirrored();
+        turned.blackout_region_col_pigeonhole();
+        assert_eq!(turned.cells, board.rotated_90().mirrored().cells);
+
+        // and upside down, with the band against the bottom edge
+        let mut flipped = banded().rotated_90().rotated_90().mirrored();
+        flipped.blackout_region_row_pigeonhole();
+        assert_eq!(
+            flipped.cells,
+            board.rotated_90().rotated_90().mirrored().cells
+        );
+
+        // and turned the other way, with the band against the right edge
+        let mut turned = banded().rotated_90();
+        turned.blackout_region_col_pigeonhole();
+        assert_eq!(turned.cells, board.rotated_90().cells);
+
+        // if region 1 only wants one star, the band wants more than they owe
+        let mut board = banded();
+        board.region_star_counts[1] = 1;
+        board.blackout_region_row_pigeonhole();
+        assert!(board
+            .cells
+            .iter()
+            .flatten()
+            .all(|cell| cell.state.is_blank()));
+    }
+
+    #[test]
+    fn test_two_regions_saturate_edge_lines() {
+        // regions 0 and 1 sit in the bottom two rows and owe them all four
+        // stars, so region 3's cells there are shaded
+        let mut board = Board::build(
+            6,
+            6,
+            vec![
+                vec![2, 2, 2, 3, 3, 3],
+                vec![2, 2, 2, 3, 3, 3],
+                vec![2, 2, 2, 3, 3, 3],
+                vec![2, 2, 2, 3, 3, 3],
+                vec![0, 0, 1, 1, 1, 3],
+                vec![0, 0, 1, 1, 1, 3],
+            ],
+        );
+        let mut turned = board.rotated_90().mirrored();
+        board.blackout_region_row_pigeonhole();
+        let blocked = |board: &Board| {
+            board
+                .cells
+                .iter()
+                .enumerate()
+                .flat_map(|(row, cells)| {
+                    cells
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, cell)| cell.state.is_blocked())
+                        .map(move |(col, _)| (row, col))
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(blocked(&board), vec![(4, 5), (5, 5)]);
+
+        // the same two regions in the rightmost two columns
+        turned.blackout_region_col_pigeonhole();
+        assert_eq!(blocked(&turned), vec![(5, 4), (5, 5)]);
+    }
+
+    #[test]
+    fn test_small_square_region() {
+        // region 0 is a T lying on its side in a 3x3 box, so it has to be
+        // read down its column rather than across its rows
+        let mut board = Board::build(
+            5,
+            5,
+            vec![
+                vec![1, 1, 1, 1, 1],
+                vec![1, 0, 1, 1, 1],
+                vec![1, 0, 0, 0, 1],
+                vec![1, 0, 1, 1, 1],
+                vec![1, 1, 1, 1, 1],
+            ],
+        );
+        board.eliminate_middle_of_small_empty_region(0);
+        // the centre touches every other cell of the region
+        assert_eq!(board.cells[2][2].state, CellState::Filled);
+        // every seating has a star in column 1, or beside the tip of the T
+        assert_eq!(board.cells[2][0].state, CellState::Filled);
+        assert_eq!(board.cells[1][2].state, CellState::Filled);
+        assert_eq!(board.cells[3][2].state, CellState::Filled);
+        assert_eq!(board.cells[1][0].state, CellState::Blank);
+        assert_eq!(board.cells[2][4].state, CellState::Blank);
+        for (row, col) in [(1, 1), (2, 1), (3, 1), (2, 3)] {
+            assert_eq!(board.cells[row][col].state, CellState::Blank);
+        }
+    }
+
+    #[test]
+    fn test_solve_with_search() {
+        /// Guesses on the last blank of the roomiest unit, the opposite of `Mrv`.
+        struct Roomiest(std::cell::Cell<usize>);
+
+        impl SearchHeuristic for Roomiest {
+            fn choose(&self, candidates: &[BranchCandidate]) -> Option<(Unit, (usize, usize))> {
+                self.0.set(self.0.get() + 1);
+                let candidate = candidates
+                    .iter()
+                    .max_by_key(|candidate| candidate.blanks.len())?;
+                Some((candidate.unit, *candidate.blanks.last()?))
+            }
+        }
+
+        let mut board = test_board_sample();
+        let expected = board.find_solution().unwrap();
+
+        let roomiest = Roomiest(std::cell::Cell::new(0));
+        assert_eq!(board.solve_with_search(&roomiest), SolveOutcome::Solved);
+        assert!(roomiest.0.get() > 0);
+        assert_eq!(board.cells, expected.cells);
+
+        let mut board = test_board_sample();
+        assert_eq!(board.solve_with_search(&Mrv), SolveOutcome::Solved);
+        assert_eq!(board.cells, expected.cells);
+
+        let candidates = [
+            BranchCandidate {
+                unit: Unit::Row(0),
+                need: 2,
+                blanks: vec![(0, 0), (0, 2), (0, 4), (0, 6)],
+            },
+            BranchCandidate {
+                unit: Unit::Col(3),
+                need: 2,
+                blanks: vec![(1, 3), (5, 3), (7, 3)],
+            },
+        ];
+        assert_eq!(Mrv.choose(&candidates), Some((Unit::Col(3), (1, 3))));
+    }
+
+    #[test]
+    fn test_degenerate_small_regions() {
+        // a region along the top edge, one cell tall, and its mirror image
+        // down the left edge, one cell wide
+        let flat = Board::build(3, 3, vec![vec![0, 0, 0], vec![1, 1, 1], vec![1, 1, 1]]);
+        for mut board in [flat.clone(), flat.rotated_90().rotated_90().rotated_90()] {
+            board.eliminate_middle_of_small_empty_region(0);
+            let region = board.regions[0].clone();
+            let (ends, middle) = ([region[0], region[2]], region[1]);
+            assert_eq!(board.cells[middle.0][middle.1].state, CellState::Filled);
+            for (row, col) in ends {
+                assert_eq!(board.cells[row][col].state, CellState::Blank);
+            }
+        }
+
+        // two cells in a corner can't seat two stars, and nothing breaks finding that out
+        let mut corner = Board::build(2, 2, vec![vec![0, 0], vec![1, 1]]);
+        corner.eliminate_middle_of_small_empty_region(0);
+        assert!(corner
+            .cells
+            .iter()
+            .flatten()
+            .all(|cell| cell.state == CellState::Blank));
+
+        let mut single = Board::build(1, 1, vec![vec![0]]);
+        single.eliminate_middle_of_small_empty_regions();
+        assert_eq!(single.find_solution(), None);
+        assert_eq!(single.count_solutions(1), 0);
+    }
+
+    #[test]
+    fn test_solved_regions() {
+        let board = test_board_stolen_1();
+        assert!(board.solved_regions().is_empty());
+        assert_eq!(board.unsolved_regions(), (0..10).collect::<Vec<_>>());
+
+        let solved = board.find_solution().unwrap();
+        assert_eq!(solved.solved_regions(), (0..10).collect::<Vec<_>>());
+        assert!(solved.unsolved_regions().is_empty());
+
+        // a region with both stars but a blank left over isn't done yet
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.star_cell(0, 0);
+        board.star_cell(2, 0);
+        for (row, col) in [(0, 1), (1, 0), (1, 1), (2, 1), (3, 0)] {
+            board.shade_cell(row, col);
+        }
+        assert!(board.solved_regions().is_empty());
+        board.shade_cell(3, 1);
+        board.regenerate_regions();
+        assert_eq!(board.solved_regions(), vec![0]);
+        assert_eq!(board.unsolved_regions(), vec![1]);
+    }
+
+    #[test]
+    fn test_is_legal() {
+        let mut board = Board::build(6, 6, vec![vec![0, 0, 0, 1, 1, 1]; 6]);
+        assert!(board.is_legal());
+        board.set_state(0, 0, CellState::Star);
+        board.set_state(0, 2, CellState::Star);
+        assert!(board.is_legal());
+
+        let mut crowded = board.clone();
+        crowded.set_state(0, 4, CellState::Star);
+        assert!(!crowded.is_legal());
+
+        let mut touching = board.clone();
+        touching.set_state(1, 3, CellState::Star);
+        assert!(!touching.is_legal());
+        assert_eq!(touching.solve(), SolveOutcome::Contradiction);
+        assert_eq!(touching.hint(), None);
+        assert_eq!(touching.difficulty().rating, Rating::Hard);
+
+        let board = test_board_stolen_1();
+        assert!(board.is_legal());
+        assert!(board.find_solution().unwrap().is_legal());
+    }
+
+    #[test]
+    fn test_touching_stars_are_a_contradiction() {
+        let mut board = test_board_stolen_1();
+        board.star_cell(4, 4);
+        board.star_cell(5, 5);
+        board.blackout_star_adjacencies();
+        assert_eq!(board.cells[4][5].state, CellState::Filled);
+        assert_eq!(
+            board.contradiction(),
+            Some(Contradiction::StarsTouch((4, 4), (5, 5)))
+        );
+        assert_eq!(board.clone().solve(), SolveOutcome::Contradiction);
+        assert_eq!(board.clone().solve_complete(), SolveOutcome::Contradiction);
+        assert_eq!(board.find_solution(), None);
+        assert_eq!(board.count_solutions(2), 0);
+    }
+
+    #[test]
+    fn test_prune_unsatisfiable_region_cells() {
+        let mut board = Board::build(
+            8,
+            8,
+            vec![
+                vec![0, 0, 0, 0, 2, 2, 2, 2],
+                vec![0, 0, 0, 0, 2, 2, 2, 2],
+                vec![0, 0, 0, 0, 2, 2, 2, 2],
+                vec![1, 1, 1, 1, 3, 3, 3, 3],
+                vec![4, 4, 4, 4, 3, 3, 3, 3],
+                vec![4, 4, 4, 4, 5, 5, 5, 5],
+                vec![6, 6, 6, 6, 5, 5, 5, 5],
+                vec![6, 6, 6, 6, 7, 7, 7, 7],
             ],
-        )
+        );
+        assert!(board.prune_unsatisfiable_region_cells());
+        // a star over or under region 1 leaves it no two cells apart
+        for row in [2, 4] {
+            for col in 0..4 {
+                assert_eq!(board.cells[row][col].state, CellState::Filled);
+            }
+        }
+        assert_eq!(board.cells[2][4].state, CellState::Blank);
+        assert!(!board.regions[0].contains(&(2, 0)));
     }
-    fn test_board_stolen_1() -> Board {
-        Board::new(
-            10,
-            10,
+
+    #[test]
+    fn test_cannot_seat_big_unit() {
+        // listing every way to seat twenty stars in a hundred cells would never
+        // finish; finding one is quick
+        let board = Board::empty(10, 10);
+        assert!(!board.cannot_seat(Unit::Region(0)));
+        assert!(!board.cannot_seat(Unit::Row(0)));
+    }
+
+    #[test]
+    fn test_is_solved() {
+        let mut board = test_board_stolen_1();
+        assert!(!board.is_solved());
+        let solution = board.find_solution().unwrap();
+        assert!(solution.is_solved());
+        assert_eq!(board.solve_complete(), SolveOutcome::Solved);
+        assert!(board.is_solved());
+
+        // every unit full, but two stars touch
+        let mut touching = Board::build(2, 2, vec![vec![0, 1]; 2]);
+        touching.set_stars_per_unit(1);
+        touching.star_cell(0, 0);
+        touching.star_cell(1, 1);
+        touching.shade_cell(0, 1);
+        touching.shade_cell(1, 0);
+        assert!(!touching.is_solved());
+
+        // a blank left over, even in a unit that has its stars
+        let mut unfinished = solution.clone();
+        let (row, col) = (0..10)
+            .map(|col| (0, col))
+            .find(|&(row, col)| unfinished.cells[row][col].state == CellState::Filled)
+            .unwrap();
+        unfinished.set_state(row, col, CellState::Blank);
+        assert!(!unfinished.is_solved());
+        assert_eq!(unfinished.outcome(), SolveOutcome::Stuck);
+    }
+
+    #[test]
+    fn test_verify_against() {
+        let solution = test_board_stolen_1().find_solution().unwrap();
+        let mut attempt = test_board_stolen_1();
+        assert_eq!(attempt.verify_against(&solution), Ok(()));
+        attempt.solve();
+        assert_eq!(attempt.verify_against(&solution), Ok(()));
+
+        let mut attempt = test_board_stolen_1();
+        attempt.star_cell(0, 1);
+        attempt.star_cell(0, 0);
+        attempt.shade_cell(0, 2);
+        assert_eq!(attempt.verify_against(&solution), Err(vec![(0, 0)]));
+        attempt.set_state(0, 1, CellState::Filled);
+        assert_eq!(attempt.verify_against(&solution), Err(vec![(0, 0), (0, 1)]));
+
+        // a smaller answer key can't vouch for the cells it doesn't have
+        let small = Board::build(2, 2, vec![vec![0, 0]; 2]);
+        assert_eq!(
+            attempt.verify_against(&small),
+            Err(vec![(0, 0), (0, 1), (0, 2)])
+        );
+    }
+
+    #[test]
+    fn test_move_history() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        let fresh = board.clone();
+        let star = Move {
+            row: 0,
+            col: 0,
+            from: CellState::Blank,
+            to: CellState::Star,
+        };
+        let shade = Move {
+            row: 1,
+            col: 0,
+            from: CellState::Blank,
+            to: CellState::Filled,
+        };
+        board.apply_move(star).unwrap();
+        board.apply_move(shade).unwrap();
+        assert!(!board.regions[0].contains(&(1, 0)));
+        let played = board.clone();
+
+        assert_eq!(board.undo(), Ok(Some(shade)));
+        assert_eq!(board.cells[1][0].state, CellState::Blank);
+        assert_eq!(board.undo(), Ok(Some(star)));
+        assert_eq!(board.undo(), Ok(None));
+        assert_eq!(board, fresh);
+        assert_eq!(board.redo(), Ok(Some(star)));
+        assert_eq!(board.redo(), Ok(Some(shade)));
+        assert_eq!(board.redo(), Ok(None));
+        assert_eq!(board, played);
+
+        // a fresh move drops whatever was undone
+        board.undo().unwrap();
+        let unstar = Move {
+            from: CellState::Star,
+            to: CellState::Blank,
+            ..star
+        };
+        board.apply_move(unstar).unwrap();
+        assert_eq!(board.redo(), Ok(None));
+        assert_eq!(board, fresh);
+
+        assert_eq!(
+            board.apply_move(Move { row: 4, ..star }),
+            Err(BoardError::OutOfBounds((4, 0)))
+        );
+        assert_eq!(
+            board.apply_move(unstar),
+            Err(BoardError::MoveMismatch {
+                cell: (0, 0),
+                expected: CellState::Star,
+                found: CellState::Blank
+            })
+        );
+        assert_eq!(board.undo(), Ok(Some(unstar)));
+
+        // solving shades cells the moves left blank, so it forgets them
+        let mut solved = test_board_stolen_1();
+        solved.apply_move(star).unwrap();
+        solved.solve();
+        assert_eq!(solved.undo(), Ok(None));
+
+        // a move that no longer fits the board stays put
+        board.set_state(0, 0, CellState::Blank);
+        assert_eq!(
+            board.undo(),
+            Err(BoardError::MoveMismatch {
+                cell: (0, 0),
+                expected: CellState::Star,
+                found: CellState::Blank
+            })
+        );
+        board.set_state(0, 0, CellState::Star);
+        assert_eq!(board.undo(), Ok(Some(star)));
+
+        // turned boards start with no moves of their own
+        board.apply_move(star).unwrap();
+        assert_eq!(board.rotated_90().undo(), Ok(None));
+        assert_eq!(board.mirrored().redo(), Ok(None));
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.remaining_blanks(), 100);
+        assert_eq!(board.progress(), 0.0);
+
+        board.star_cell(0, 1);
+        board.shade_cell(5, 5);
+        assert_eq!(board.remaining_blanks(), 98);
+        assert_eq!(board.progress(), 0.02);
+
+        let mut last = board.progress();
+        for step in board.steps() {
+            assert!(step.progress() >= last);
+            last = step.progress();
+        }
+        board.solve_complete();
+        assert_eq!(board.remaining_blanks(), 0);
+        assert_eq!(board.progress(), 1.0);
+        assert_eq!(Board::build(0, 0, vec![]).progress(), 1.0);
+    }
+
+    #[test]
+    fn test_solve_bounded() {
+        let mut expected = test_board_stolen_1();
+        expected.solve();
+        let passes = expected.report().outer_passes;
+        assert!(passes >= 2);
+
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.solve_bounded(0), SolveOutcome::Stuck);
+        assert_eq!(board, test_board_stolen_1());
+
+        assert_eq!(board.solve_bounded(1), SolveOutcome::Stuck);
+        assert_eq!(board.report().outer_passes, 1);
+        assert_ne!(board, test_board_stolen_1());
+
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.solve_bounded(passes), expected.outcome());
+        assert_eq!(board, expected);
+        assert_eq!(board.report(), expected.report());
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let mut board = test_board_stolen_1();
+        let stats = board.solve_with_stats();
+        let mut solved = test_board_stolen_1();
+        solved.solve();
+        assert_eq!(board, solved);
+        assert_eq!(stats.rounds, solved.report().outer_passes);
+        assert!(stats.per_rule.values().all(|&count| count > 0));
+        assert_eq!(stats.stars_placed, board.stars().count());
+        let shaded = board
+            .cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.state == CellState::Filled)
+            .count();
+        assert_eq!(stats.cells_shaded, shaded);
+
+        // a second go finds nothing new in its one pass
+        let again = board.solve_with_stats();
+        assert_eq!(
+            (again.rounds, again.stars_placed, again.cells_shaded),
+            (1, 0, 0)
+        );
+        assert!(again.per_rule.is_empty());
+
+        // the lone cell of region 0 takes a star, and the shading around it
+        // is put down to adjacency rather than to the star rule
+        let mut board = Board::build(
+            4,
+            4,
             vec![
-                vec![0, 0, 1, 1, 2, 2, 2, 3, 3, 3],
-                vec![0, 0, 1, 2, 2, 2, 2, 3, 2, 3],
-                vec![0, 0, 1, 1, 1, 1, 2, 2, 2, 3],
-                vec![0, 1, 1, 1, 4, 4, 5, 2, 2, 2],
-                vec![0, 1, 1, 1, 4, 5, 5, 5, 5, 2],
-                vec![0, 6, 6, 6, 4, 4, 4, 4, 2, 2],
-                vec![7, 6, 7, 7, 4, 4, 4, 8, 8, 2],
-                vec![7, 7, 7, 7, 4, 9, 9, 9, 8, 2],
-                vec![7, 7, 7, 8, 8, 8, 8, 8, 8, 2],
-                vec![7, 7, 7, 8, 8, 8, 8, 8, 8, 8],
+                vec![1, 0, 1, 1],
+                vec![1, 1, 1, 1],
+                vec![2, 2, 2, 2],
+                vec![3, 3, 3, 3],
             ],
-        )
+        );
+        board.set_stars_per_unit(1);
+        board.rules = RuleSet::ROWS | RuleSet::COLS | RuleSet::REGIONS | RuleSet::ADJACENCY;
+        let stats = board.solve_with_stats();
+        assert!(board.is_solved());
+        assert_eq!(stats.stars_placed, 4);
+        assert_eq!(stats.cells_shaded, 12);
+        assert!(stats.per_rule[&RuleKind::StarAdjacency] > 0);
+        assert_eq!(stats.per_rule[&RuleKind::RequiredStarRegion], 1);
     }
-    fn solved_board_stolen_1() -> Board {
-        Board::solved(
-            10,
-            10,
+
+    #[test]
+    fn test_empty() {
+        let mut board = Board::empty(10, 10);
+        assert_eq!(board.dimensions(), (10, 10));
+        assert_eq!(board.num_regions(), 1);
+        assert_eq!(board.region_star_quota(0), Some(20));
+        assert_eq!(board.remaining_blanks(), 100);
+        assert!(board.is_legal());
+
+        // row 0 fills up and shades the rest, and the region never overflows
+        for col in [1, 3] {
+            board
+                .apply_move(Move {
+                    row: 0,
+                    col,
+                    from: CellState::Blank,
+                    to: CellState::Star,
+                })
+                .unwrap();
+        }
+        board.solve();
+        assert!(board.cells[0]
+            .iter()
+            .all(|cell| cell.state != CellState::Blank));
+        assert_eq!(board.outcome(), SolveOutcome::Stuck);
+        assert!(!board.rules.contains(RuleSet::HIDDEN_SINGLE));
+
+        assert_eq!(Board::empty(0, 0).dimensions(), (0, 0));
+
+        // a different star count keeps the region owed the whole board's stars
+        let mut three = Board::empty(10, 10);
+        three.set_stars_per_unit(3);
+        assert_eq!(three.region_star_quota(0), Some(30));
+        assert_eq!(three.validate_star_totals(), Ok(()));
+
+        // and the search finishes it, with the region rules off
+        let mut board = Board::empty(8, 8);
+        assert_eq!(board.solve_complete(), SolveOutcome::Solved);
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn test_empty_round_trip() {
+        let mut board = Board::empty(4, 4);
+        board.star_cell(0, 1);
+        let compact = Board::from_compact(&board.to_compact()).unwrap();
+        assert_eq!(compact, board);
+        assert_eq!(compact.rules, board.rules);
+        assert_eq!(compact.region_star_quota(0), Some(8));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_empty_serde_round_trip() {
+        let board = Board::empty(6, 6);
+        let json = serde_json::to_string(&board).unwrap();
+        let back = serde_json::from_str::<Board>(&json).unwrap();
+        assert_eq!(back, board);
+        assert_eq!(back.rules, board.rules);
+    }
+
+    #[test]
+    fn test_solve_stops_at_contradiction() {
+        // row 0 has a star and nothing left for its second
+        let mut board = test_board_stolen_1();
+        board.star_cell(0, 1);
+        for col in (0..10).filter(|&col| col != 1) {
+            board.shade_cell(0, col);
+        }
+        board.regenerate_regions();
+        assert_eq!(
+            board.contradiction(),
+            Some(Contradiction::TooFewBlanks {
+                unit: Unit::Row(0),
+                need: 1,
+                blanks: vec![]
+            })
+        );
+        assert_eq!(board.solve(), SolveOutcome::Contradiction);
+        assert_eq!(board.report().outer_passes, 1);
+        assert_eq!(board.solve_complete(), SolveOutcome::Contradiction);
+    }
+
+    #[test]
+    fn test_unsolvable_board_reports_contradiction() {
+        // a well-formed grid with no solution: rules run into a contradiction
+        let regions = vec![
+            vec![7, 7, 3, 3, 1, 1, 1, 2],
+            vec![7, 7, 3, 3, 1, 1, 2, 2],
+            vec![7, 7, 3, 3, 1, 2, 2, 2],
+            vec![7, 0, 2, 2, 2, 2, 2, 6],
+            vec![0, 0, 2, 2, 4, 4, 5, 6],
+            vec![0, 0, 4, 4, 4, 4, 5, 6],
+            vec![0, 0, 4, 4, 4, 4, 5, 6],
+            vec![0, 0, 0, 4, 4, 4, 5, 5],
+        ];
+        let board = Board::try_new(8, 8, regions).unwrap();
+        assert_eq!(board.clone().solve(), SolveOutcome::Contradiction);
+        assert_eq!(board.clone().solve_complete(), SolveOutcome::Contradiction);
+        assert_eq!(board.count_solutions(1), 0);
+    }
+
+    #[test]
+    fn test_stars() {
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.stars().count(), 0);
+        board.solve_complete();
+        let stars = board.stars().collect::<Vec<_>>();
+        assert_eq!(stars.len(), 20);
+        assert!(stars.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(stars[..2], [(0, 1), (0, 3)]);
+
+        for (tag, _cells) in board.iter_regions() {
+            let in_region = board.stars_in_region(tag).collect::<Vec<_>>();
+            assert_eq!(in_region.len(), 2);
+            assert!(in_region
+                .iter()
+                .all(|&(row, col)| board.cells[row][col].region == tag));
+        }
+        assert_eq!(board.stars_in_region(10).count(), 0);
+    }
+
+    #[test]
+    fn test_add_hidden_singles() {
+        let mut board = Board::build(
+            5,
+            5,
             vec![
-                (1, 3),
-                (5, 7),
-                (2, 9),
-                (4, 6),
-                (0, 8),
-                (3, 6),
-                (1, 9),
-                (5, 7),
-                (0, 2),
-                (4, 8),
+                vec![0, 0, 1, 1, 1],
+                vec![0, 0, 1, 1, 1],
+                vec![0, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1],
+                vec![1, 1, 1, 1, 1],
             ],
-        )
+        );
+        board.shade_cell(0, 1);
+        board.regenerate_regions();
+        // four blanks for two stars is too many for the region rules to go on
+        let mut regional = board.clone();
+        regional.add_required_stars_region();
+        regional.add_last_region_star();
+        assert_eq!(regional.cells, board.cells);
+
+        // R2C1 touches every other blank and R2C2 touches both of the rest,
+        // so R1C1 and R3C1 are the only seating left
+        board.add_hidden_singles();
+        assert_eq!(board.cells[0][0].state, CellState::Star);
+        assert_eq!(board.cells[2][0].state, CellState::Star);
+        assert_eq!(board.cells[1][0].state, CellState::Filled);
+        assert_eq!(board.cells[1][1].state, CellState::Filled);
     }
 
     #[test]
-    fn test_constructor() {
-        test_board_stolen_1();
+    fn test_solved_fixture_sizes() {
+        let six = Board::solved(
+            6,
+            6,
+            vec![vec![0], vec![2], vec![4], vec![1], vec![3], vec![5]],
+        );
+        assert_eq!(six.cells.len(), 6);
+        assert!(six.cells.iter().all(|row| row.len() == 6));
+        assert_eq!(six.total_stars(), 6);
+
+        let regions = vec![
+            vec![0, 0, 1, 1, 1],
+            vec![1, 1, 1, 4, 4],
+            vec![1, 3, 4, 4, 2],
+            vec![3, 3, 4, 4, 2],
+            vec![4, 4, 4, 4, 4],
+        ];
+        let solution = Board::solved(5, 5, vec![vec![0], vec![2], vec![4], vec![1], vec![3]]);
+        let mut board = Board::with_stars_per_unit(5, 5, regions, 1);
+        assert_eq!(board.solve_assuming(&solution), Ok(SolveOutcome::Solved));
     }
 
     #[test]
-    fn test_solve() {
-        // let mut board = test_board_sample();
-        // board.solve();
-        // board.print();
+    fn test_solve_assuming() {
+        let solution = solved_board_stolen_1();
+        let mut board = test_board_stolen_1();
+        assert_eq!(board.solve_assuming(&solution), Ok(SolveOutcome::Stuck));
+
+        // the small-region rule shades R2C9 first thing
+        let mut wrong = solution.clone();
+        wrong.set_state(1, 8, CellState::Star);
+        let mut board = test_board_stolen_1();
+        let Err(BoardError::Diverged(divergence)) = board.solve_assuming(&wrong) else {
+            panic!("expected a divergence");
+        };
+        assert_eq!(
+            divergence,
+            Divergence {
+                rule: Some(RuleKind::SmallRegion),
+                row: 1,
+                col: 8,
+                found: CellState::Filled,
+                expected: CellState::Star,
+            }
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "eliminate_middle_of_small_empty_regions made R2C9 empty, \
+             but the solution has it a star"
+        );
+
+        let (row, col) = (
+            0,
+            (0..10)
+                .find(|&col| solution.cells[0][col].state == CellState::Star)
+                .unwrap(),
+        );
+        let mut board = test_board_stolen_1();
+        board.shade_cell(row, col);
+        let Err(BoardError::Diverged(divergence)) = board.solve_assuming(&solution) else {
+            panic!("expected a divergence");
+        };
+        assert_eq!(divergence.rule, None);
+        assert_eq!((divergence.row, divergence.col), (row, col));
+        assert_eq!(
+            BoardError::Diverged(divergence.clone()).to_string(),
+            divergence.to_string()
+        );
+
+        // a solution of another size is turned away before anything runs
         let mut board = test_board_stolen_1();
+        assert_eq!(
+            board.solve_assuming(&Board::empty(4, 4)),
+            Err(BoardError::DimensionMismatch {
+                expected: (10, 10),
+                found: (4, 4),
+            })
+        );
+        assert_eq!(board, test_board_stolen_1());
+    }
+
+    #[test]
+    fn test_contradiction() {
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        assert_eq!(board.contradiction(), None);
+
+        board.shade_cell(0, 0);
+        board.shade_cell(0, 1);
+        let contradiction = board.contradiction().unwrap();
+        assert_eq!(
+            contradiction,
+            Contradiction::BlanksTouch {
+                unit: Unit::Row(0),
+                need: 2,
+                blanks: vec![(0, 2), (0, 3)]
+            }
+        );
+        assert_eq!(
+            contradiction.to_string(),
+            "row 0 needs 2 more stars but only (0, 2) and (0, 3) remain, and they touch"
+        );
+        assert_eq!(contradiction.cells(), vec![(0, 2), (0, 3)]);
+
+        board.shade_cell(0, 2);
+        assert_eq!(
+            board.contradiction().unwrap().to_string(),
+            "row 0 needs 2 more stars but only (0, 3) remains"
+        );
+
+        board.star_cell(2, 2);
+        board.star_cell(3, 3);
+        assert_eq!(
+            board.contradiction(),
+            Some(Contradiction::StarsTouch((2, 2), (3, 3)))
+        );
+        assert_eq!(board.is_contradictory(), board.contradiction().is_some());
+    }
+
+    #[test]
+    fn test_iter_regions() {
+        let mut board = Board::build(4, 2, vec![vec![0, 0, 3, 3], vec![0, 3, 3, 3]]);
+        let regions = board.iter_regions().collect::<Vec<_>>();
+        assert_eq!(
+            regions,
+            vec![
+                (0, &[(0, 0), (0, 1), (1, 0)][..]),
+                (3, &[(0, 2), (0, 3), (1, 1), (1, 2), (1, 3)][..]),
+            ]
+        );
+
+        board.mark_empty(1, 1).unwrap();
+        let (tag, cells) = board.iter_regions().nth(1).unwrap();
+        assert_eq!(tag, 3);
+        assert!(!cells.contains(&(1, 1)));
+        assert!(cells
+            .iter()
+            .all(|&(row, col)| board.cells[row][col].region == tag));
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let mut board = Board::build(4, 2, vec![vec![0, 0, 1, 1]; 2]);
+        board.star_cell(0, 0);
+        board.shade_cell(1, 1);
+
+        let svg = board.to_svg(32);
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"132\" height=\"68\""));
+        assert_eq!(svg, board.to_svg_with(&SvgOptions::default()));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 9);
+        assert_eq!(svg.matches("<circle ").count(), 1);
+        assert_eq!(svg.matches("fill=\"#bbb\"").count(), 1);
+        // the only region border is down the middle
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(!svg.contains("data-row") && !svg.contains("<text"));
+
+        let svg = board.to_svg_with(&SvgOptions {
+            cell_px: 10,
+            show_region_tags: true,
+            interactive: true,
+        });
+        assert!(svg.contains("width=\"44\""));
+        assert!(svg.contains(r#"x="20" y="10" width="10" height="10""#));
+        assert!(svg.contains(r#"data-row="1" data-col="2" data-region="1""#));
+        assert_eq!(svg.matches("<text ").count(), 8);
+        assert!(board.to_svg(10).contains("width=\"44\""));
+    }
+
+    #[test]
+    fn test_to_bordered_string() {
+        let mut board = Board::build(4, 2, vec![vec![0, 0, 1, 1]; 2]);
+        board.star_cell(0, 0);
+        board.shade_cell(1, 1);
+        assert_eq!(
+            board.to_bordered_string(),
+            "┏━━━┯━━━┳━━━┯━━━┓\n\
+             ┃ X │ · ┃ · │ · ┃\n\
+             ┠───┼───╂───┼───┨\n\
+             ┃ · │ # ┃ · │ · ┃\n\
+             ┗━━━┷━━━┻━━━┷━━━┛\n"
+        );
+
+        let board = Board::build(2, 2, vec![vec![0, 1], vec![1, 1]]);
+        assert_eq!(
+            board.to_bordered_string(),
+            "┏━━━┳━━━┓\n\
+             ┃ · ┃ · ┃\n\
+             ┣━━━╃───┨\n\
+             ┃ · │ · ┃\n\
+             ┗━━━┷━━━┛\n"
+        );
+    }
+
+    #[test]
+    fn test_rotated_and_mirrored() {
+        let mut board = Board::build(3, 2, vec![vec![0, 0, 1], vec![0, 1, 1]]);
+        board.star_cell(0, 0);
+        board.shade_cell(1, 2);
+
+        let rotated = board.rotated_90();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        let tags = |board: &Board| {
+            board
+                .cells
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.region).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tags(&rotated), vec![vec![0, 0], vec![1, 0], vec![1, 1]]);
+        assert_eq!(rotated.cells[0][1].state, CellState::Star);
+        assert_eq!(rotated.cells[2][0].state, CellState::Filled);
+        assert_eq!(rotated.rotated_90().rotated_90().rotated_90(), board);
+
+        let mirrored = board.mirrored();
+        assert_eq!(tags(&mirrored), vec![vec![1, 0, 0], vec![1, 1, 0]]);
+        assert_eq!(mirrored.cells[0][2].state, CellState::Star);
+        assert_eq!(mirrored.mirrored(), board);
+
+        // the solver shouldn't care which way up the puzzle is
+        let mut solved = test_board_stolen_1();
+        let mut turned = solved.rotated_90().mirrored();
+        solved.solve();
+        turned.solve();
+        assert_eq!(turned, solved.rotated_90().mirrored());
+    }
+
+    #[test]
+    fn test_solution_labels() {
         let solution = solved_board_stolen_1();
-        board.add_solution(solution);
-        board.solve();
-        board.print();
-        // let mut board = test_board_sample();
-        // board.solve();
-        // board.print();
+        let labels = solution.solution_labels(1);
+        assert_eq!(labels.len(), 20);
+        assert_eq!(labels[..4], ["R1C2", "R1C4", "R2C6", "R2C8"]);
+        assert_eq!(solution.solution_labels(0)[0], "R0C1");
+        assert!(test_board_stolen_1().solution_labels(1).is_empty());
+    }
+
+    #[test]
+    fn test_wrapping_adjacencies() {
+        unordered_eq(wrapping_adjacencies(10, 10, 10, 10), vec![]);
+        unordered_eq(
+            wrapping_adjacencies(10, 10, 0, 0),
+            vec![
+                (9, 9),
+                (9, 0),
+                (9, 1),
+                (0, 9),
+                (0, 1),
+                (1, 9),
+                (1, 0),
+                (1, 1),
+            ],
+        );
+        unordered_eq(
+            wrapping_adjacencies(10, 10, 5, 5),
+            adjacencies(10, 10, 5, 5),
+        );
+        unordered_eq(
+            wrapping_adjacencies(2, 2, 0, 0),
+            vec![(0, 1), (1, 0), (1, 1)],
+        );
+        unordered_eq(wrapping_adjacencies(1, 3, 1, 0), vec![(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_wrap_shades_across_edges() {
+        let mut board = test_board_sample();
+        board.set_wrap(true);
+        board.star_cell(0, 0);
+        board.blackout_star_adjacencies();
+        for (row, col) in [(9, 9), (9, 0), (0, 9), (1, 9), (9, 1)] {
+            assert_eq!(board.cells[row][col].state, CellState::Filled);
+        }
+
+        let mut board = test_board_sample();
+        board.star_cell(0, 0);
+        board.blackout_star_adjacencies();
+        assert_eq!(board.cells[9][9].state, CellState::Blank);
+    }
+
+    #[test]
+    fn test_cell_state_classification() {
+        for state in [CellState::Blank, CellState::Star, CellState::Filled] {
+            let classes = [state.is_blank(), state.is_star(), state.is_blocked()];
+            assert_eq!(classes.iter().filter(|class| **class).count(), 1);
+        }
+        assert!(CellState::Star.is_star());
+        assert!(CellState::Filled.is_blocked());
+    }
+
+    #[test]
+    fn test_orthogonal_adjacency() {
+        unordered_eq(
+            orthogonal_adjacencies(10, 10, 5, 5),
+            vec![(4, 5), (6, 5), (5, 4), (5, 6)],
+        );
+        unordered_eq(orthogonal_adjacencies(10, 10, 0, 0), vec![(1, 0), (0, 1)]);
+
+        let mut board = Board::build(4, 4, vec![vec![0, 0, 1, 1]; 4]);
+        board.set_adjacency_mode(AdjacencyMode::Orthogonal);
+        board.star_cell(1, 1);
+        board.star_cell(2, 2);
+        assert!(!board.stars_touch());
+        board.blackout_star_adjacencies();
+        for (row, col) in [(0, 1), (1, 0), (1, 2), (2, 1), (3, 2), (2, 3)] {
+            assert_eq!(board.cells[row][col].state, CellState::Filled);
+        }
+        for (row, col) in [(0, 0), (0, 2), (2, 0), (3, 3)] {
+            assert_eq!(board.cells[row][col].state, CellState::Blank);
+        }
+
+        // three blanks on a diagonal have to hold region 0's two stars at the
+        // ends, unless the diagonal ones are allowed to touch
+        let diagonal = |adjacency| {
+            let mut board = Board::build(3, 3, vec![vec![0, 1, 1], vec![1, 0, 1], vec![1, 1, 0]]);
+            board.set_adjacency_mode(adjacency);
+            board.add_required_stars_in_region(0, Board::add_star_locally);
+            [(0, 0), (1, 1), (2, 2)].map(|(row, col)| board.cells[row][col].state)
+        };
+        assert_eq!(
+            diagonal(AdjacencyMode::King),
+            [CellState::Star, CellState::Filled, CellState::Star]
+        );
+        assert_eq!(diagonal(AdjacencyMode::Orthogonal), [CellState::Blank; 3]);
     }
 
     #[test]
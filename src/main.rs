@@ -0,0 +1,89 @@
+//! Solves a puzzle read from stdin, given as a region grid (see
+//! `Board::from_str`), and prints the board it ends up with and how it went.
+
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use twonottouch::{Board, CellState};
+
+const USAGE: &str = "usage: twonottouch [--svg] [--trace] [--unique] < puzzle
+
+  --svg     print the board as SVG instead of text
+  --trace   print each deduction as it's made
+  --unique  report whether the puzzle has exactly one solution";
+
+/// How big each cell is drawn with `--svg`, in pixels.
+const SVG_CELL_PX: u32 = 32;
+
+#[derive(Default)]
+struct Options {
+    svg: bool,
+    trace: bool,
+    unique: bool,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("twonottouch: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut options = Options::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--svg" => options.svg = true,
+            "--trace" => options.trace = true,
+            "--unique" => options.unique = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("unknown option `{arg}`\n{USAGE}")),
+        }
+    }
+
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|error| format!("couldn't read the puzzle from stdin: {error}"))?;
+    let mut board = input
+        .parse::<Board>()
+        .map_err(|error| format!("couldn't read the puzzle: {error}"))?;
+
+    if options.unique {
+        match board.count_solutions(2) {
+            0 => println!("no solutions"),
+            1 => println!("exactly one solution"),
+            _ => println!("more than one solution"),
+        }
+    }
+    if options.trace {
+        for deduction in board.clone().solve_with_trace() {
+            let change = match deduction.new_state {
+                CellState::Star => "star",
+                _ => "empty",
+            };
+            println!(
+                "R{}C{} {change}: {}",
+                deduction.row + 1,
+                deduction.col + 1,
+                deduction.rule
+            );
+        }
+    }
+    let outcome = board.solve();
+    if options.svg {
+        println!("{}", board.to_svg(SVG_CELL_PX));
+    } else {
+        println!("{board}");
+    }
+    println!("{outcome:?}");
+    Ok(())
+}
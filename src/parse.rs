@@ -0,0 +1,110 @@
+//! Reading region maps out of plain text.
+
+use std::str::FromStr;
+
+use crate::{Board, BoardError};
+
+/// Reads one row per line, each cell a single region glyph: `0`-`9`, then `a`-`z`
+/// for boards with more than ten regions. Capitals are turned away rather than
+/// read as their lowercase letter; `Board::from_char_grid` takes those. Spaces
+/// between cells are optional.
+pub(crate) fn parse_region_grid(s: &str) -> Result<Vec<Vec<usize>>, BoardError> {
+    let rows = s
+        .trim()
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| {
+                    c.to_digit(36)
+                        .filter(|_region| !c.is_ascii_uppercase())
+                        .map(|region| region as usize)
+                        .ok_or_else(|| {
+                            BoardError::ParseFailure(format!("`{c}` is not a region glyph"))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_rectangular(rows)
+}
+
+/// Reads one row per line, each cell any character other than whitespace or
+/// a comma, either of which may separate cells. Every distinct character is
+/// a region, tagged in the order they first turn up, so `A` and `a` differ.
+fn parse_char_grid(s: &str) -> Result<Vec<Vec<usize>>, BoardError> {
+    let mut glyphs = vec![];
+    let rows = s
+        .trim()
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|&c| !c.is_whitespace() && c != ',')
+                .map(|c| {
+                    glyphs
+                        .iter()
+                        .position(|&glyph| glyph == c)
+                        .unwrap_or_else(|| {
+                            glyphs.push(c);
+                            glyphs.len() - 1
+                        })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    check_rectangular(rows)
+}
+
+/// Turns away grids with no cells, or with rows of different lengths.
+fn check_rectangular(rows: Vec<Vec<usize>>) -> Result<Vec<Vec<usize>>, BoardError> {
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err(BoardError::ParseFailure("the grid is empty".to_string()));
+    }
+    if let Some((row, cells)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_row, cells)| cells.len() != width)
+    {
+        return Err(BoardError::RaggedRow {
+            row,
+            len: cells.len(),
+            width,
+        });
+    }
+    Ok(rows)
+}
+
+/// Reads a region grid as `parse_region_grid` does and validates it like
+/// `Board::try_from`. The tags have to run from 0 with none skipped, since a
+/// gap in pasted text is far more likely a typo than a deliberate choice.
+impl FromStr for Board {
+    type Err = BoardError;
+
+    fn from_str(s: &str) -> Result<Board, BoardError> {
+        let regions = parse_region_grid(s)?;
+        let mut tags = regions.iter().flatten().copied().collect::<Vec<_>>();
+        tags.sort_unstable();
+        tags.dedup();
+        if let Some((missing, _tag)) = tags
+            .into_iter()
+            .enumerate()
+            .find(|(index, tag)| index != tag)
+        {
+            return Err(BoardError::ParseFailure(format!(
+                "no region is tagged {missing}"
+            )));
+        }
+        Board::try_from(regions)
+    }
+}
+
+impl Board {
+    /// Reads a region grid drawn in letters, or any other characters, as
+    /// puzzles copied from forums usually are: `AABBB`, `A A B B B` or
+    /// `A,A,B,B,B` on each line. Regions are tagged in the order their
+    /// characters first appear. Validated like `Board::try_from`.
+    pub fn from_char_grid(s: &str) -> Result<Board, BoardError> {
+        Board::try_from(parse_char_grid(s)?)
+    }
+}
@@ -0,0 +1,115 @@
+//! The run-length `.sbn` puzzle format shared by several Star Battle apps.
+//!
+//! A puzzle is a `WxH` size header, a `:`, and then the region map in row-major
+//! order as runs of region letters: `10x10:3A4B2C1D...`. A run is an optional
+//! decimal count followed by a letter; a bare letter is a run of one.
+//! Regions are lettered `A`-`Z` and then `a`-`z`. Whitespace in the body is ignored.
+
+use crate::{Board, BoardError};
+
+const REGION_LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+impl Board {
+    /// Parses a puzzle from its `.sbn` encoding. Every cell starts blank.
+    /// Regions that come out in more than one piece are rejected.
+    pub fn from_sbn(s: &str) -> Result<Board, BoardError> {
+        let (header, body) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| parse_failure("missing `WxH:` size header"))?;
+        let (width, height): (usize, usize) = header
+            .trim()
+            .split_once(['x', 'X'])
+            .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+            .ok_or_else(|| parse_failure(format!("malformed size header `{header}`")))?;
+        if width == 0 || height == 0 {
+            return Err(parse_failure("board dimensions must be non-zero"));
+        }
+
+        let mut tags = Vec::with_capacity(width * height);
+        let mut count: Option<usize> = None;
+        for c in body.chars().filter(|c| !c.is_whitespace()) {
+            if let Some(digit) = c.to_digit(10) {
+                count = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit as usize));
+                if count.is_none() {
+                    return Err(parse_failure("run length overflowed"));
+                }
+                continue;
+            }
+            let region = region_of_letter(c)
+                .ok_or_else(|| parse_failure(format!("`{c}` is not a region letter")))?;
+            let run = count.take().unwrap_or(1);
+            if run == 0 {
+                return Err(parse_failure(format!("zero-length run of `{c}`")));
+            }
+            if tags.len() + run > width * height {
+                return Err(parse_failure(format!(
+                    "runs cover more than the {width}x{height} grid"
+                )));
+            }
+            tags.extend(std::iter::repeat_n(region, run));
+        }
+        if count.is_some() {
+            return Err(parse_failure("run length is missing its region letter"));
+        }
+        if tags.len() != width * height {
+            return Err(parse_failure(format!(
+                "runs cover {} cells but the grid has {}",
+                tags.len(),
+                width * height
+            )));
+        }
+
+        let regions = tags
+            .chunks(width)
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+        Board::try_from(regions)
+    }
+
+    /// Encodes the region map in `.sbn` form. Cell states are not recorded.
+    ///
+    /// # Panics
+    ///
+    /// If the board uses a region tag that has no `.sbn` letter (52 or above).
+    pub fn to_sbn(&self) -> String {
+        let mut result = format!("{}x{}:", self.width, self.height);
+        let mut tags = self
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.region)
+            .peekable();
+        while let Some(region) = tags.next() {
+            let mut run = 1;
+            while tags.next_if_eq(&region).is_some() {
+                run += 1;
+            }
+            if run > 1 {
+                result.push_str(&run.to_string());
+            }
+            result.push(letter_of_region(region));
+        }
+        result
+    }
+}
+
+fn parse_failure(reason: impl Into<String>) -> BoardError {
+    BoardError::ParseFailure(reason.into())
+}
+
+fn region_of_letter(c: char) -> Option<usize> {
+    REGION_LETTERS
+        .iter()
+        .position(|&letter| letter as char == c)
+}
+
+fn letter_of_region(region: usize) -> char {
+    match REGION_LETTERS.get(region) {
+        Some(&letter) => letter as char,
+        None => panic!("region {region} has no .sbn letter"),
+    }
+}
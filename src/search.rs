@@ -0,0 +1,284 @@
+//! Backtracking for boards the deduction rules can't finish on their own.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{history::MoveHistory, Board, CellState, RuleSet, SolveOutcome, Unit};
+
+impl Board {
+    /// Solves a copy of the board, guessing and backtracking whenever the
+    /// deductions in `solve` run dry, and returns it. The board itself is left as is.
+    /// Returns `None` if there's no way to finish the board from its current state.
+    pub fn find_solution(&self) -> Option<Board> {
+        self.guess().search(&AtomicBool::new(false), &Mrv).ok()
+    }
+
+    /// Solves the board, searching when the deductions run dry, unless `cancel`
+    /// gets set first. It's checked between deduction passes and before every
+    /// guess, so another thread can stop a hopeless search almost immediately.
+    /// A cancelled board keeps whatever the deductions had found.
+    pub fn solve_with_cancel(&mut self, cancel: &AtomicBool) -> SolveOutcome {
+        self.solve_searching(cancel, &Mrv)
+    }
+
+    /// Solves the board in place: the deductions first, then a search that
+    /// stars the most constrained blank and backs out of any guess that ends in
+    /// a contradiction. This always finishes the puzzle if it can be finished.
+    pub fn solve_complete(&mut self) -> SolveOutcome {
+        self.solve_searching(&AtomicBool::new(false), &Mrv)
+    }
+
+    /// Solves the board like `find_solution` does, but lets `heuristic` pick
+    /// which blank each guess is made on. The default, `Mrv`, is what every
+    /// other solving method uses.
+    pub fn solve_with_search(&mut self, heuristic: &dyn SearchHeuristic) -> SolveOutcome {
+        self.solve_searching(&AtomicBool::new(false), heuristic)
+    }
+
+    fn solve_searching(
+        &mut self,
+        cancel: &AtomicBool,
+        heuristic: &dyn SearchHeuristic,
+    ) -> SolveOutcome {
+        loop {
+            if !self.deduce(cancel) {
+                return SolveOutcome::Cancelled;
+            }
+            match self.outcome() {
+                SolveOutcome::Stuck => {}
+                outcome => return outcome,
+            }
+            if !self.prune_unsatisfiable_region_cells() {
+                break;
+            }
+        }
+
+        match self.guess().search(cancel, heuristic) {
+            Ok(solution) => {
+                self.cells = solution.cells;
+                self.regions = solution.regions;
+                self.outcome()
+            }
+            Err(outcome) => outcome,
+        }
+    }
+
+    /// Shades every blank that can't be a star because starring it, and
+    /// shading around it, would leave some row, column or region unable to
+    /// seat the stars it's still missing without two of them touching. Each
+    /// blank gets a trial board of its own, so this is far too slow for the
+    /// deduction loop and only runs before a search. Returns whether it shaded
+    /// anything.
+    pub(crate) fn prune_unsatisfiable_region_cells(&mut self) -> bool {
+        let mut changed = false;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells[row][col].state != CellState::Blank {
+                    continue;
+                }
+                let mut trial = self.guess();
+                trial.star_cell(row, col);
+                trial.blackout_around_star(row, col);
+                if trial.is_contradictory()
+                    || trial
+                        .units()
+                        .any(|unit| self.rules_cover(unit) && trial.cannot_seat(unit))
+                {
+                    changed |= self.shade_cell(row, col);
+                }
+            }
+        }
+        if changed {
+            self.regenerate_regions();
+        }
+        changed
+    }
+
+    /// Whether the rules in play look at `unit`, so that `Board::empty`'s one
+    /// big region, with the region rules off, is left out of the pruning.
+    fn rules_cover(&self, unit: Unit) -> bool {
+        self.rules.contains(match unit {
+            Unit::Row(_) => RuleSet::ROWS,
+            Unit::Col(_) => RuleSet::COLS,
+            Unit::Region(_) => RuleSet::REGIONS,
+        })
+    }
+
+    /// Whether no way of seating the stars a unit is missing among its blanks
+    /// keeps them all apart.
+    pub(crate) fn cannot_seat(&self, unit: Unit) -> bool {
+        let (stars, _blanks) = self.unit_counts(unit);
+        let need = self.quota(unit).saturating_sub(stars);
+        let blanks = self
+            .unit_cells(unit)
+            .into_iter()
+            .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+            .collect::<Vec<_>>();
+        need > 0 && !self.has_seating(&blanks, need)
+    }
+
+    /// How many ways there are to finish the board from its current state,
+    /// counting no further than `limit`. The board itself is left as is.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.guess().count(limit)
+    }
+
+    /// Whether the puzzle has exactly one solution, which is what makes it fair.
+    /// This searches for a second solution after finding the first, so it costs
+    /// a good deal more than `solve`, or even `find_solution`.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// A copy of the board to make guesses on, which are supposed to be wrong some of the time.
+    fn guess(&self) -> Board {
+        let board = Board {
+            history: MoveHistory::default(),
+            ..self.clone()
+        };
+        #[cfg(test)]
+        let board = Board {
+            solution: None,
+            ..board
+        };
+        board
+    }
+
+    /// The first solution found from here, or else `Contradiction` if there
+    /// isn't one or `Cancelled` if `cancel` was set along the way.
+    fn search(
+        mut self,
+        cancel: &AtomicBool,
+        heuristic: &dyn SearchHeuristic,
+    ) -> Result<Board, SolveOutcome> {
+        if !self.deduce(cancel) || cancel.load(Ordering::Relaxed) {
+            return Err(SolveOutcome::Cancelled);
+        }
+        if self.is_contradictory() {
+            return Err(SolveOutcome::Contradiction);
+        }
+        let Some((row, col)) = self
+            .branch_cell(heuristic)
+            .or_else(|| self.first_blank_anywhere())
+        else {
+            // no blanks, and no unit over or under its quota
+            return Ok(self);
+        };
+
+        let mut guess = self.clone();
+        guess.star_cell(row, col);
+        match guess.search(cancel, heuristic) {
+            Err(SolveOutcome::Contradiction) => {}
+            result => return result,
+        }
+        self.shade_cell(row, col);
+        self.search(cancel, heuristic)
+    }
+
+    /// `search`, except that it keeps going after a solution until it has seen `limit`.
+    fn count(mut self, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        self.deduce(&AtomicBool::new(false));
+        if self.is_contradictory() {
+            return 0;
+        }
+        let Some((row, col)) = self
+            .most_constrained_blank()
+            .or_else(|| self.first_blank_anywhere())
+        else {
+            return 1;
+        };
+
+        let mut guess = self.clone();
+        guess.star_cell(row, col);
+        let found = guess.count(limit);
+        if found == limit {
+            return found;
+        }
+        self.shade_cell(row, col);
+        found + self.count(limit - found)
+    }
+
+    /// The first blank in row-major order. Once every unit has its stars, the
+    /// blanks left over still need guessing on when the rules that would have
+    /// shaded them are switched off.
+    pub(crate) fn first_blank_anywhere(&self) -> Option<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .find(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+    }
+
+    /// The first blank of the unit with the fewest blanks per star it still needs.
+    pub(crate) fn most_constrained_blank(&self) -> Option<(usize, usize)> {
+        self.branch_cell(&Mrv)
+    }
+
+    /// The blank `heuristic` wants to guess on next, or `Mrv`'s choice if it
+    /// doesn't name a blank. `None` once no unit is missing any stars.
+    fn branch_cell(&self, heuristic: &dyn SearchHeuristic) -> Option<(usize, usize)> {
+        let candidates = self.branch_candidates();
+        if candidates.is_empty() {
+            return None;
+        }
+        heuristic
+            .choose(&candidates)
+            .map(|(_unit, cell)| cell)
+            .filter(|&(row, col)| {
+                row < self.height
+                    && col < self.width
+                    && self.cells[row][col].state == CellState::Blank
+            })
+            .or_else(|| Mrv.choose(&candidates).map(|(_unit, cell)| cell))
+    }
+
+    /// Every unit still missing stars, with its blanks.
+    fn branch_candidates(&self) -> Vec<BranchCandidate> {
+        self.units()
+            .filter_map(|unit| {
+                let (stars, _blanks) = self.unit_counts(unit);
+                let need = self.quota(unit).saturating_sub(stars);
+                let blanks = self
+                    .unit_cells(unit)
+                    .into_iter()
+                    .filter(|&(row, col)| self.cells[row][col].state == CellState::Blank)
+                    .collect::<Vec<_>>();
+                (need > 0 && !blanks.is_empty()).then_some(BranchCandidate { unit, need, blanks })
+            })
+            .collect()
+    }
+}
+
+/// A row, column or region that's still missing stars, as the search sees it
+/// when it's about to guess.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchCandidate {
+    pub unit: Unit,
+    /// how many more stars the unit needs
+    pub need: usize,
+    /// the unit's undecided cells, in row-major order
+    pub blanks: Vec<(usize, usize)>,
+}
+
+/// Decides where the search makes its next guess. The search stars the chosen
+/// cell first and shades it if that leads nowhere, so any blank works, but
+/// one that settles a lot either way keeps the search short.
+pub trait SearchHeuristic {
+    /// The unit to branch on and one of its blanks, picked from `candidates`,
+    /// which is never empty. Anything other than a blank falls back to `Mrv`.
+    fn choose(&self, candidates: &[BranchCandidate]) -> Option<(Unit, (usize, usize))>;
+}
+
+/// Minimum remaining values: the first blank of the unit with the fewest
+/// blanks per star it still needs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Mrv;
+
+impl SearchHeuristic for Mrv {
+    fn choose(&self, candidates: &[BranchCandidate]) -> Option<(Unit, (usize, usize))> {
+        candidates
+            .iter()
+            .min_by_key(|candidate| candidate.blanks.len() * 2 / candidate.need)
+            .map(|candidate| (candidate.unit, candidate.blanks[0]))
+    }
+}
@@ -0,0 +1,62 @@
+//! Serde support, behind the `serde` feature, for keeping boards mid-solve in
+//! JSON or any other format serde speaks.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AdjacencyMode, Board, BoardError, Cell};
+
+/// What a board is stored as: its size, its cells, and the rules it's played
+/// by. The region index is rebuilt from the cells on the way back in rather
+/// than trusted, and nothing about how the board is being solved is kept.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct StoredBoard {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<Cell>>,
+    wrap: bool,
+    adjacency: AdjacencyMode,
+    stars_per_unit: usize,
+    region_star_counts: Vec<usize>,
+}
+
+impl From<Board> for StoredBoard {
+    fn from(board: Board) -> Self {
+        Self {
+            width: board.width,
+            height: board.height,
+            cells: board.cells,
+            wrap: board.wrap,
+            adjacency: board.adjacency,
+            stars_per_unit: board.stars_per_unit,
+            region_star_counts: board.region_star_counts,
+        }
+    }
+}
+
+/// Validated like `Board::try_from`, from the cells' region tags, except that
+/// a `Board::empty` comes back as one.
+impl TryFrom<StoredBoard> for Board {
+    type Error = BoardError;
+
+    fn try_from(stored: StoredBoard) -> Result<Self, Self::Error> {
+        let tags = stored
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.region).collect())
+            .collect::<Vec<_>>();
+        let mut board = Board::from_stored_regions(tags)?;
+        if (board.width, board.height) != (stored.width, stored.height) {
+            return Err(BoardError::DimensionMismatch {
+                expected: (stored.width, stored.height),
+                found: (board.width, board.height),
+            });
+        }
+        board.set_wrap(stored.wrap);
+        board.set_adjacency_mode(stored.adjacency);
+        board.set_stars_per_unit(stored.stars_per_unit);
+        board.set_region_star_counts(stored.region_star_counts)?;
+        board.cells = stored.cells;
+        board.regenerate_regions();
+        Ok(board)
+    }
+}
@@ -0,0 +1,126 @@
+//! Vector rendering for web pages. No dependencies: it's all `format!`.
+
+use std::fmt::Write;
+
+use crate::{Board, CellState};
+
+/// How `to_svg_with` draws a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// The side of each cell, in pixels.
+    pub cell_px: u32,
+    /// Whether to print each cell's region tag in its corner.
+    pub show_region_tags: bool,
+    /// Whether each cell's rect carries `data-row`, `data-col` and `data-region`
+    /// attributes, for a script to hang click handlers on.
+    pub interactive: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_px: 32,
+            show_region_tags: false,
+            interactive: false,
+        }
+    }
+}
+
+/// Room around the grid so the outer border isn't clipped.
+const MARGIN: u32 = 2;
+const THIN: u32 = 1;
+const THICK: u32 = 3;
+
+impl Board {
+    /// The board as a standalone SVG document with `cell_px` pixel cells, and
+    /// otherwise the default `SvgOptions`.
+    pub fn to_svg(&self, cell_px: u32) -> String {
+        self.to_svg_with(&SvgOptions {
+            cell_px,
+            ..SvgOptions::default()
+        })
+    }
+
+    /// The board as a standalone SVG document: thin lines between cells, thick
+    /// ones between regions and around the edge, stars as dots and shaded cells in grey.
+    pub fn to_svg_with(&self, options: &SvgOptions) -> String {
+        let px = options.cell_px;
+        let (width, height) = (self.width as u32 * px, self.height as u32 * px);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="{o} {o} {w} {h}">"#,
+            w = width + 2 * MARGIN,
+            h = height + 2 * MARGIN,
+            o = -(MARGIN as i64),
+        );
+
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let (x, y) = (col as u32 * px, row as u32 * px);
+                let fill = match cell.state {
+                    CellState::Filled => "#bbb",
+                    CellState::Blank | CellState::Star => "#fff",
+                };
+                let data = if options.interactive {
+                    format!(
+                        r#" data-row="{row}" data-col="{col}" data-region="{}""#,
+                        cell.region
+                    )
+                } else {
+                    String::new()
+                };
+                let _ = writeln!(
+                    svg,
+                    r##"<rect x="{x}" y="{y}" width="{px}" height="{px}" fill="{fill}" stroke="#999" stroke-width="{THIN}"{data}/>"##
+                );
+                if cell.state == CellState::Star {
+                    let _ = writeln!(
+                        svg,
+                        r##"<circle cx="{}" cy="{}" r="{}" fill="#000"/>"##,
+                        x + px / 2,
+                        y + px / 2,
+                        px * 3 / 10
+                    );
+                }
+                if options.show_region_tags {
+                    let _ = writeln!(
+                        svg,
+                        r##"<text x="{}" y="{}" font-size="{}" fill="#666">{}</text>"##,
+                        x + px / 10,
+                        y + px * 3 / 10,
+                        px / 4,
+                        cell.region
+                    );
+                }
+            }
+        }
+
+        // region borders go over the cells so the thin lines don't cut into them
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let region = self.region_at(row, col);
+                let (x, y) = (col as u32 * px, row as u32 * px);
+                if col + 1 < self.width && self.region_at(row, col + 1) != region {
+                    line(&mut svg, (x + px, y), (x + px, y + px));
+                }
+                if row + 1 < self.height && self.region_at(row + 1, col) != region {
+                    line(&mut svg, (x, y + px), (x + px, y + px));
+                }
+            }
+        }
+        let _ = writeln!(
+            svg,
+            r##"<rect x="0" y="0" width="{width}" height="{height}" fill="none" stroke="#000" stroke-width="{THICK}"/>"##
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn line(svg: &mut String, (x1, y1): (u32, u32), (x2, y2): (u32, u32)) {
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="#000" stroke-width="{THICK}" stroke-linecap="square"/>"##
+    );
+}
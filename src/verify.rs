@@ -0,0 +1,102 @@
+//! Checking the deductions against an answer that's already known, for
+//! catching solver bugs with a trusted set of solved puzzles.
+
+use std::fmt::Display;
+
+use crate::{explain::label, Board, BoardError, CellState, RuleKind, SolveOutcome};
+
+/// The first cell where `Board::solve_assuming` saw the deductions part ways
+/// with the supplied solution, which it reports as `BoardError::Diverged`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The deduction that set the cell, or `None` if the board disagreed
+    /// with the solution before any deduction ran.
+    pub rule: Option<RuleKind>,
+    pub row: usize,
+    pub col: usize,
+    /// What the deduction made of the cell.
+    pub found: CellState,
+    /// What the solution has there.
+    pub expected: CellState,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |state: CellState| match state {
+            CellState::Star => "a star",
+            CellState::Filled => "empty",
+            CellState::Blank => "blank",
+        };
+        let cell = label(self.row, self.col);
+        match self.rule {
+            Some(rule) => write!(f, "{rule} made {cell} {}", describe(self.found))?,
+            None => write!(f, "{cell} starts out {}", describe(self.found))?,
+        }
+        write!(f, ", but the solution has it {}", describe(self.expected))
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+impl Board {
+    /// Runs the deductions one at a time, checking after each that every cell
+    /// it has decided agrees with `solution`, and gives back the first one that
+    /// doesn't. A star deduction also runs the shading rules it sets off, so a
+    /// divergence put down to it may have come from one of those. Nothing is
+    /// guessed: a board that's sound but needs a search ends up `Stuck`.
+    /// A `solution` of another size is a `DimensionMismatch`, and the board
+    /// is left alone.
+    pub fn solve_assuming(&mut self, solution: &Board) -> Result<SolveOutcome, BoardError> {
+        if (solution.width, solution.height) != (self.width, self.height) {
+            return Err(BoardError::DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (solution.width, solution.height),
+            });
+        }
+        self.diverges_from(solution, None)
+            .map_err(BoardError::Diverged)?;
+        while let Some((rule, _reason, _before)) = self.apply_next_rule() {
+            self.diverges_from(solution, Some(rule))
+                .map_err(BoardError::Diverged)?;
+        }
+        Ok(self.outcome())
+    }
+
+    /// Grades an attempt against `solution`: every cell that's been decided
+    /// and disagrees with it, in row-major order. Blanks are never wrong, so a
+    /// half-finished board that's on track passes. Where the boards aren't the
+    /// same size, decided cells outside `solution` count as wrong too.
+    pub fn verify_against(&self, solution: &Board) -> Result<(), Vec<(usize, usize)>> {
+        let wrong = (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                let found = self.cells[row][col].state;
+                found != CellState::Blank && solution.cell_state(row, col) != Some(found)
+            })
+            .collect::<Vec<_>>();
+        if wrong.is_empty() {
+            Ok(())
+        } else {
+            Err(wrong)
+        }
+    }
+
+    fn diverges_from(&self, solution: &Board, rule: Option<RuleKind>) -> Result<(), Divergence> {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let found = self.cells[row][col].state;
+                let expected = solution.cells[row][col].state;
+                if found != CellState::Blank && found != expected {
+                    return Err(Divergence {
+                        rule,
+                        row,
+                        col,
+                        found,
+                        expected,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
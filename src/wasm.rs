@@ -0,0 +1,61 @@
+//! Entry points that are safe to call from a browser: no printing, no panics.
+//! Only built with the `wasm` feature.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::Board;
+
+/// Solves a puzzle given as a region grid (see `Board::from_str`) and returns
+/// the solved board, one row per line, or a description of what went wrong.
+#[wasm_bindgen]
+pub fn solve_from_grid(grid: &str) -> Result<String, String> {
+    let board = grid.parse::<Board>().map_err(|error| error.to_string())?;
+    board
+        .find_solution()
+        .map(|solution| solution.to_string())
+        .ok_or_else(|| "the puzzle has no solution".to_string())
+}
+
+/// A board for JavaScript to hold on to between calls. Regions cross the
+/// boundary as one flat array of tags plus the width, or as JSON, since
+/// nested vectors can't.
+#[wasm_bindgen]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[wasm_bindgen]
+impl WasmBoard {
+    /// A blank board from its region tags listed row by row, `width` to a row.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, regions: &[usize]) -> Result<WasmBoard, String> {
+        Board::from_flat(width, regions)
+            .map(|board| WasmBoard { board })
+            .map_err(|error| error.to_string())
+    }
+
+    /// A blank board from its region tags as a JSON array of rows, like
+    /// `[[0, 0, 1], [0, 1, 1], [2, 2, 2]]`.
+    pub fn from_json(regions: &str) -> Result<WasmBoard, String> {
+        let regions =
+            serde_json::from_str::<Vec<Vec<usize>>>(regions).map_err(|error| error.to_string())?;
+        Board::try_from(regions)
+            .map(|board| WasmBoard { board })
+            .map_err(|error| error.to_string())
+    }
+
+    /// Finishes the board, searching if the deductions aren't enough, and
+    /// returns it one row per line. A board with no solution is left as it was.
+    pub fn solve(&mut self) -> Result<String, String> {
+        self.board = self
+            .board
+            .find_solution()
+            .ok_or_else(|| "the puzzle has no solution".to_string())?;
+        Ok(self.board.to_string())
+    }
+
+    /// The board as it stands, in the same JSON `serde` stores it as.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.board).map_err(|error| error.to_string())
+    }
+}